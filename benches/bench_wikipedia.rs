@@ -1,12 +1,16 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use lithtml::Dom;
 
-static HTML: &'static str = include_str!("./wikipedia-simple.html");
+static HTML: &str = include_str!("./wikipedia-simple.html");
 //static HTML: &'static str = include_str!("./wikipedia-2020-12-21.html");
 
 fn wikipedia(c: &mut Criterion) {
     let mut group = c.benchmark_group("wikipedia");
     group.bench_function("simple", |b| b.iter(|| Dom::parse(HTML).unwrap()));
+    let dom = Dom::parse(HTML).unwrap();
+    group.bench_function("simple_deep_clone_owned", |b| {
+        b.iter(|| dom.deep_clone_owned())
+    });
     group.finish();
 }
 
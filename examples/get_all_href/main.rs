@@ -5,7 +5,7 @@ use lithtml::{Dom, Node, Result};
 fn main() -> Result<()> {
     let html = include_str!("./index.html");
     let dom = Dom::parse(html)?;
-    let iter = dom.children.get(0).unwrap().into_iter();
+    let iter = dom.children.first().unwrap().into_iter();
 
     let hrefs = iter.filter_map(|item| match item {
         Node::Element(ref element) if element.name == "a" => element.attributes["href"].clone(),
@@ -0,0 +1,125 @@
+use super::element::{Element, ElementVariant};
+use super::node::{self, Node};
+use super::{Dom, DomVariant};
+use crate::{Error, Result};
+use std::borrow::Cow;
+
+/// Builds a dom from start, text, comment and end events, the write-side counterpart of
+/// `Dom::events`. Every started element has to be ended, also void elements like `<br>`.
+#[derive(Debug, Default)]
+pub struct DomBuilder<'s> {
+    children: Vec<Node<'s>>,
+    // the started elements, which aren't ended yet
+    open: Vec<Element<'s>>,
+}
+
+impl<'s> DomBuilder<'s> {
+    /// Create a builder without nodes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an element with the attributes, a `class` attribute is split into the classes.
+    /// The variant is `Void` for `Element::VOID_ELEMENTS` and `RawText` for `script`, `style`,
+    /// `title` and `textarea`. Returns an error when the current element is void.
+    pub fn push_start(
+        &mut self,
+        name: impl Into<Cow<'s, str>>,
+        attributes: &[(&'s str, Option<&'s str>)],
+    ) -> Result<&mut Self> {
+        self.check_parent()?;
+        let mut element = Element {
+            name: name.into(),
+            depth: self.open.len(),
+            ..Element::default()
+        };
+        for &(key, value) in attributes {
+            match (key, value) {
+                (key, Some(classes)) if key.eq_ignore_ascii_case("class") => {
+                    node::push_classes(&mut element, classes.into())
+                }
+                _ => {
+                    element.attributes.insert(key.into(), value.map(Into::into));
+                }
+            }
+        }
+        element.variant = if element.matches_any_tag(Element::VOID_ELEMENTS) {
+            ElementVariant::Void
+        } else if element.matches_any_tag(&["script", "style", "title", "textarea"]) {
+            ElementVariant::RawText
+        } else {
+            ElementVariant::Normal
+        };
+        self.open.push(element);
+        Ok(self)
+    }
+
+    /// Add a text node to the current element, see `DomBuilder::push_start`
+    pub fn push_text(&mut self, text: impl Into<Cow<'s, str>>) -> Result<&mut Self> {
+        self.push_node(Node::Text(text.into()))
+    }
+
+    /// Add a comment to the current element, see `DomBuilder::push_start`
+    pub fn push_comment(&mut self, comment: impl Into<Cow<'s, str>>) -> Result<&mut Self> {
+        self.push_node(Node::Comment(comment.into()))
+    }
+
+    /// End the current element, returns an error when no element is started
+    pub fn push_end(&mut self) -> Result<&mut Self> {
+        let element = self
+            .open
+            .pop()
+            .ok_or_else(|| Error::Manipulation("An end without a started element".to_string()))?;
+        self.push_node(Node::Element(element))
+    }
+
+    /// Return the dom, which is a document when the only element at the top level is `html`.
+    /// Returns an error when an element isn't ended.
+    pub fn finish(self) -> Result<Dom<'s>> {
+        if let Some(element) = self.open.last() {
+            return Err(Error::Manipulation(format!(
+                "The element {} isn't ended",
+                element.name
+            )));
+        }
+
+        let mut elements = self.children.iter().filter_map(Node::element);
+        let tree_type = match (elements.next(), elements.next()) {
+            _ if self.children.is_empty() => DomVariant::Empty,
+            (Some(html), None)
+                if html.tag_eq_ignore_ascii_case("html")
+                    && !self
+                        .children
+                        .iter()
+                        .any(|n| matches!(n, Node::Text(_) | Node::CData(_))) =>
+            {
+                DomVariant::Document
+            }
+            _ => DomVariant::DocumentFragment,
+        };
+        Ok(Dom {
+            tree_type,
+            children: self.children,
+            ..Dom::default()
+        })
+    }
+
+    fn push_node(&mut self, node: Node<'s>) -> Result<&mut Self> {
+        self.check_parent()?;
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.children.push(node),
+        }
+        Ok(self)
+    }
+
+    /// Void elements can't have children
+    fn check_parent(&self) -> Result<()> {
+        match self.open.last() {
+            Some(parent) if parent.variant == ElementVariant::Void => Err(Error::Manipulation(
+                format!("The void element {} can't have children", parent.name),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,14 +1,49 @@
-use super::node::Node;
-use super::options::FormattingOptions;
+use super::entities::{encode_attribute, encode_text};
+use super::intern;
+use super::node::{
+    fmt_collapsed_text, fmt_wrapped_words, normalize_nodes, retain_nodes, shift_depths, Node,
+};
+use super::options::{AttributeOrder, FormattingOptions, Syntax};
+use super::selector::Selector;
 use super::span::SourceSpan;
-use serde::{Deserialize, Serialize, Serializer};
+use indexmap::IndexMap;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::default::Default;
 use std::fmt::Display;
 use std::result::Result;
 
-/// Normal: `<div></div>` or Void: `<meta/>`and `<meta>`
+/// The words of inline content, which are wrapped into lines, see `Element::push_inline_words`
+#[derive(Default)]
+struct InlineWords {
+    words: Vec<String>,
+    // the next text starts a new word, because whitespace was added
+    space: bool,
+}
+
+impl InlineWords {
+    /// Add the text to the last word, unless whitespace was added before
+    fn push_str(&mut self, text: &str) {
+        match self.words.last_mut() {
+            Some(word) if !self.space => word.push_str(text),
+            _ => self.words.push(text.to_string()),
+        }
+        self.space = false;
+    }
+
+    /// Add the words of the text, the whitespace of the text separates the words
+    fn push_text(&mut self, text: &str) {
+        self.space |= text.starts_with(char::is_whitespace);
+        for (i, word) in text.split_whitespace().enumerate() {
+            self.space |= i > 0;
+            self.push_str(&encode_text(word));
+        }
+        self.space |= text.ends_with(char::is_whitespace);
+    }
+}
+
+/// Normal: `<div></div>`, Void: `<meta/>`and `<meta>` or RawText: `<script></script>`
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 // TODO: Align with: https://html.spec.whatwg.org/multipage/syntax.html#elements-2
@@ -17,22 +52,49 @@ pub enum ElementVariant {
     Normal,
     /// A void element can't have children, ex: <meta /> and <meta>
     Void,
+    /// A raw text element has only a raw text child, which isn't html, ex: <script></script>,
+    /// <style></style>, <title></title> and <textarea></textarea>
+    RawText,
 }
 
-pub type Attributes<'s> = HashMap<Cow<'s, str>, Option<Cow<'s, str>>>;
+/// The text direction of the `dir` attribute, see `Dom::effective_dir`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dir {
+    /// Left to right, the default direction
+    #[default]
+    Ltr,
+    /// Right to left, ex: `<p dir="rtl">`
+    Rtl,
+    /// The direction is detected from the content, ex: `<p dir="auto">`
+    Auto,
+}
+
+impl Dir {
+    /// Parse the value of a `dir` attribute case-insensitive, `None` for invalid values
+    pub fn parse(value: &str) -> Option<Self> {
+        [("ltr", Dir::Ltr), ("rtl", Dir::Rtl), ("auto", Dir::Auto)]
+            .into_iter()
+            .find(|(name, _)| value.trim().eq_ignore_ascii_case(name))
+            .map(|(_, dir)| dir)
+    }
+}
+
+/// The attributes of an element, stored in the order they appear in the source
+pub type Attributes<'s> = IndexMap<Cow<'s, str>, Option<Cow<'s, str>>>;
 
 /// Most of the parsed html nodes are elements, except for text
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Element<'s> {
     /// The name / tag of the element
+    #[serde(borrow, deserialize_with = "deserialize_name")]
     pub name: Cow<'s, str>,
 
     /// The element variant, if it is of type void or not
     pub variant: ElementVariant,
 
     /// All of the elements attributes, except id and class
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
     #[serde(serialize_with = "ordered_map")]
     #[serde(default)]
     pub attributes: Attributes<'s>,
@@ -50,6 +112,12 @@ pub struct Element<'s> {
     #[serde(skip)]
     #[serde(default)]
     pub source_span: SourceSpan<'s>,
+
+    /// The nesting depth of the element when it was parsed, elements at the top level have the
+    /// depth `0` and their children `1`. It isn't updated when the tree is changed.
+    #[serde(skip)]
+    #[serde(default)]
+    pub depth: usize,
 }
 
 impl<'s> Element<'s> {
@@ -57,9 +125,19 @@ impl<'s> Element<'s> {
     where
         W: std::fmt::Write,
     {
+        // without new lines the element is written minified in a single line
+        if !o.new_lines {
+            return self.fmt_inline(f, o);
+        }
+
         // write tabs for the depth
         o.fmt_depth(f, depth)?;
 
+        // preformatted content is written as it is, formatting whitespace would change it
+        if self.matches_any_tag(&["pre", "textarea"]) {
+            return self.fmt_inline_with(f, o, true);
+        }
+
         // write node start
         write!(f, "<{}", self.name)?;
 
@@ -67,7 +145,11 @@ impl<'s> Element<'s> {
         let attr_len: usize = self
             .attributes
             .iter()
-            .map(|(k, v)| k.len() + v.as_ref().map(|v| v.len()).unwrap_or(0) + 4)
+            .map(|(k, v)| match v {
+                Some(v) => k.len() + v.len() + 4,
+                None if o.expand_boolean_attributes => k.len() * 2 + 4,
+                None => k.len() + 4,
+            })
             .sum();
 
         // count classes length
@@ -83,93 +165,758 @@ impl<'s> Element<'s> {
         // print in one line or multiline with depth - depending on space
         let c_inline = if e_len > o.max_len && o.new_lines {
             let mut c_inline = String::new();
-            c_inline.push('\n');
+            o.fmt_new_line(&mut c_inline)?;
             o.fmt_depth(&mut c_inline, depth + o.tab_size as usize)?;
             c_inline
         } else {
             String::from(" ")
         };
 
-        // print the classes seperatly
-        if !self.classes.is_empty() {
-            let classes = self
-                .classes
-                .iter()
-                .enumerate()
-                .map(|(i, c)| {
-                    let c = c.trim();
-                    if c.is_empty() {
-                        String::new()
-                    } else if i == 0 {
-                        c.to_string()
-                    } else {
-                        format!(" {c}")
-                    }
-                })
-                .collect::<String>();
-            write!(f, "{0}class={1}{classes}{1}", c_inline, o.quotes())?
-        }
-
-        // print the attributes ordered
-        let ordered_attributes: BTreeMap<_, _> = self.attributes.iter().collect();
-        for (k, v) in ordered_attributes {
-            match v {
-                Some(v) => {
-                    let v = match o.double_quot {
-                        true => v.replace('\"', "\\\""),
-                        false => v.replace('\'', "\\\'"),
-                    };
-                    write!(f, "{0}{k}={1}{v}{1}", c_inline, o.quotes())?
-                }
-                None => write!(f, "{0}{k}", c_inline)?,
-            }
-        }
+        self.fmt_attributes(f, o, &c_inline)?;
 
         // end tag - continue only when not void element
-        match (
-            e_len > o.max_len,
-            self.variant == ElementVariant::Normal && !self.children.is_empty(),
-        ) {
-            (true, true) => {
-                write!(f, "\n")?;
+        match (e_len > o.max_len, self.variant == ElementVariant::Void) {
+            (true, false) => {
+                o.fmt_new_line(f)?;
                 o.fmt_depth(f, depth)?;
                 write!(f, ">")?
             }
-            (true, false) => {
-                write!(f, "\n")?;
+            (true, true) => {
+                o.fmt_new_line(f)?;
                 o.fmt_depth(f, depth)?;
-                write!(f, "/>")?;
-                return Ok(());
+                return self.fmt_void_end(f, o, "");
             }
-            (false, true) => write!(f, ">")?,
-            (false, false) => {
-                write!(f, "/>")?;
-                return Ok(());
+            (false, false) => write!(f, ">")?,
+            (false, true) => return self.fmt_void_end(f, o, " "),
+        }
+
+        // empty elements keep their end tag
+        if self.children.is_empty() {
+            return write!(f, "</{0}>", self.name);
+        }
+
+        // print the raw text of script and style elements without any encoding
+        if self.has_raw_text() {
+            for child in self.children.iter() {
+                match child.text() {
+                    Some(text) => write!(f, "{}", text)?,
+                    None => child.fmt_opt(f, o, depth + o.tab_size as usize)?,
+                }
+            }
+            write!(f, "</{0}>", self.name)?;
+            return Ok(());
+        }
+
+        // print inline content in the same line to keep the rendering unchanged, unless it's
+        // wrapped at the whitespace of the text, because it's longer than the max length
+        if self.has_inline_content(o) {
+            let mut inline = String::new();
+            for child in self.children.iter() {
+                child.fmt_inline(&mut inline, o)?;
             }
+            let len = depth + o.tab_size as usize + inline.chars().count() + self.name.len() + 3;
+            if !o.wrap_text || len <= o.max_len {
+                return write!(f, "{inline}</{0}>", self.name);
+            }
+
+            let mut words = InlineWords::default();
+            self.push_inline_words(&mut words, o)?;
+            o.fmt_new_line(f)?;
+            o.fmt_depth(f, depth + o.tab_size as usize)?;
+            fmt_wrapped_words(f, o, words.words.iter(), depth + o.tab_size as usize)?;
+            o.fmt_new_line(f)?;
+            o.fmt_depth(f, depth)?;
+            return write!(f, "</{0}>", self.name);
         }
 
         // print single text children in the same line when not too long
-        if let Some(text) = self.children.get(0).and_then(|c| c.text()) {
+        if let Some(text) = self.children.first().and_then(|c| c.text()) {
             if self.children.len() == 1
                 && depth + o.tab_size as usize + text.len() + self.name.len() + 3 <= o.max_len
             {
-                write!(f, "{}", text)?;
+                write!(f, "{}", encode_text(text))?;
                 write!(f, "</{0}>", self.name)?;
                 return Ok(());
             }
         }
 
-        // print the normal children
+        // print the normal children, whitespace between them is replaced by the new lines
         for child in self.children.iter() {
-            write!(f, "\n")?;
+            if child.text().is_some_and(|t| t.trim().is_empty()) {
+                continue;
+            }
+            o.fmt_new_line(f)?;
             child.fmt_opt(f, o, depth + o.tab_size as usize)?;
         }
-        write!(f, "\n")?;
+        o.fmt_new_line(f)?;
         o.fmt_depth(f, depth)?;
         write!(f, "</{0}>", self.name)?;
 
         Ok(())
     }
+
+    /// Write the classes and attributes, each prefixed with the separator
+    fn fmt_attributes<W>(
+        &self,
+        f: &mut W,
+        o: &FormattingOptions,
+        c_inline: &str,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        // the classes are joined to a single class attribute
+        let classes = self
+            .classes
+            .iter()
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let class = match classes.is_empty() {
+            true => None,
+            false => Some(("class", Some(classes.as_str()))),
+        };
+
+        // order the attributes, the class comes first unless it's ordered by priority
+        let mut attributes = self
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.as_ref(), v.as_deref()))
+            .collect::<Vec<_>>();
+        match &o.attribute_order {
+            AttributeOrder::Alphabetical => attributes.sort_by(|a, b| a.0.cmp(b.0)),
+            AttributeOrder::SourceOrder => (),
+            AttributeOrder::Priority(keys) => {
+                attributes.extend(class);
+                let priority = |k: &str| match keys.iter().position(|p| p == k) {
+                    Some(position) => position,
+                    None if k == "class" => keys.len(),
+                    None => keys.len() + 1,
+                };
+                attributes.sort_by(|a, b| (priority(a.0), a.0).cmp(&(priority(b.0), b.0)));
+            }
+            AttributeOrder::Custom(cmp) => attributes.sort_by(|a, b| cmp(a.0, b.0)),
+        }
+        if !matches!(o.attribute_order, AttributeOrder::Priority(_)) {
+            attributes.splice(0..0, class);
+        }
+
+        for (k, v) in attributes {
+            match v {
+                Some(v) => {
+                    let quote = attribute_quote(v, o.quotes());
+                    let v = encode_attribute(v, quote);
+                    write!(f, "{0}{k}={1}{v}{1}", c_inline, quote)?
+                }
+                None if o.expand_boolean_attributes => {
+                    write!(f, "{0}{k}={1}{k}{1}", c_inline, o.quotes())?
+                }
+                None => write!(f, "{0}{k}", c_inline)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the words of the children for wrapping, the tags of splittable inline elements are
+    /// joined to the adjacent words. Other elements like `pre` are written inline as one word.
+    fn push_inline_words(
+        &self,
+        words: &mut InlineWords,
+        o: &FormattingOptions,
+    ) -> std::fmt::Result {
+        for child in self.children.iter() {
+            match child {
+                Node::Text(text) => words.push_text(text),
+                Node::Element(e)
+                    if e.variant == ElementVariant::Normal
+                        && !e.matches_any_tag(&["pre", "textarea"]) =>
+                {
+                    let mut tag = format!("<{}", e.name);
+                    e.fmt_attributes(&mut tag, o, " ")?;
+                    tag.push('>');
+                    words.push_str(&tag);
+                    e.push_inline_words(words, o)?;
+                    words.push_str(&format!("</{}>", e.name));
+                }
+                _ => {
+                    let mut html = String::new();
+                    child.fmt_inline(&mut html, o)?;
+                    words.push_str(&html);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the element in a single line without any formatting whitespace
+    pub(super) fn fmt_inline<W>(&self, f: &mut W, o: &FormattingOptions) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        self.fmt_inline_with(f, o, false)
+    }
+
+    /// Write the end of the start tag of a void element. The html void elements have no slash in
+    /// html5, xhtml writes the slash after the separator, see `Syntax`
+    fn fmt_void_end<W>(&self, f: &mut W, o: &FormattingOptions, separator: &str) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        match o.syntax {
+            Syntax::Html5 if self.matches_any_tag(Self::VOID_ELEMENTS) => write!(f, ">"),
+            Syntax::Html5 => write!(f, "/>"),
+            Syntax::Xhtml => write!(f, "{separator}/>"),
+        }
+    }
+
+    /// Write the element in a single line, the whitespace of preformatted text is kept
+    fn fmt_inline_with<W>(
+        &self,
+        f: &mut W,
+        o: &FormattingOptions,
+        preformatted: bool,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        write!(f, "<{}", self.name)?;
+        self.fmt_attributes(f, o, " ")?;
+
+        // only void elements are self-closing, a parsed element with an end tag keeps it
+        if self.variant == ElementVariant::Void {
+            return self.fmt_void_end(f, o, " ");
+        }
+
+        write!(f, ">")?;
+        self.fmt_inline_children(f, o, preformatted)?;
+        write!(f, "</{0}>", self.name)
+    }
+
+    /// Write the children in a single line, see `Element::fmt_inline_with`
+    fn fmt_inline_children<W>(
+        &self,
+        f: &mut W,
+        o: &FormattingOptions,
+        preformatted: bool,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        let preformatted = preformatted || self.matches_any_tag(&["pre", "textarea"]);
+        let last = self.children.len().saturating_sub(1);
+        for (i, child) in self.children.iter().enumerate() {
+            match child {
+                Node::Text(text) if self.has_raw_text() => write!(f, "{}", text)?,
+                Node::Text(text) if preformatted => write!(f, "{}", encode_text(text))?,
+                // whitespace next to a block element isn't rendered
+                Node::Text(text) if text.trim().is_empty() && self.is_next_to_block(i, o) => (),
+                // whitespace at the start and end of a block element isn't rendered
+                Node::Text(text) if !o.is_inline(&self.name) => {
+                    let text = if i == 0 { text.trim_start() } else { text };
+                    let text = if i == last { text.trim_end() } else { text };
+                    fmt_collapsed_text(f, text)?
+                }
+                Node::Element(e) => e.fmt_inline_with(f, o, preformatted)?,
+                _ => child.fmt_inline(f, o)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The html of the element including its own tags, like the `outerHTML` of the browser dom.
+    /// It's written in a single line, see `FormattingOptions::compact`.
+    pub fn outer_html(&self) -> String {
+        let mut html = String::new();
+        // writing into a string can't fail
+        let _ = self.fmt_inline(&mut html, &FormattingOptions::compact());
+        html
+    }
+
+    /// The html of the children without the tags of the element, like the `innerHTML` of the
+    /// browser dom. It's written in a single line, see `FormattingOptions::compact`.
+    pub fn inner_html(&self) -> String {
+        let mut html = String::new();
+        // writing into a string can't fail
+        let _ = self.fmt_inline_children(&mut html, &FormattingOptions::compact(), false);
+        html
+    }
+
+    /// Check if the content is raw text, which is written without encoding, like scripts
+    fn has_raw_text(&self) -> bool {
+        self.variant == ElementVariant::RawText && self.matches_any_tag(&["script", "style"])
+    }
+
+    /// Check if the children are inline content, which means that they contain at least one
+    /// inline element and no block elements. Formatting whitespace would change the rendering of
+    /// such content.
+    fn has_inline_content(&self, o: &FormattingOptions) -> bool {
+        self.children.iter().any(|c| c.element().is_some())
+            && self.children.iter().all(|c| match c {
+                Node::Element(e) => o.is_inline(&e.name),
+                _ => true,
+            })
+    }
+
+    /// Check if the previous or next sibling of the child at the index is an element, which isn't
+    /// an inline element
+    fn is_next_to_block(&self, index: usize, o: &FormattingOptions) -> bool {
+        let is_block = |i: usize| matches!(self.children.get(i), Some(Node::Element(e)) if !o.is_inline(&e.name));
+        index.checked_sub(1).is_some_and(is_block) || is_block(index + 1)
+    }
+
+    /// Elements which are formatted inline by default, see `FormattingOptions::inline_elements`
+    pub const INLINE_ELEMENTS: &'static [&'static str] = &[
+        "a", "abbr", "b", "bdi", "bdo", "button", "cite", "code", "data", "del", "dfn", "em", "i",
+        "img", "input", "ins", "kbd", "label", "mark", "q", "s", "samp", "select", "small", "span",
+        "strong", "sub", "sup", "time", "u", "var",
+    ];
+
+    /// The html elements which are always void, see `ParseOptions::void_elements`
+    pub const VOID_ELEMENTS: &'static [&'static str] = &[
+        "area", "base", "br", "col", "command", "embed", "hr", "img", "input", "keygen", "link",
+        "meta", "param", "source", "track", "wbr",
+    ];
+
+    /// Check if the element name matches the given name, case-insensitive without allocating
+    pub fn tag_eq_ignore_ascii_case(&self, other: &str) -> bool {
+        self.name.eq_ignore_ascii_case(other)
+    }
+
+    /// Check if the element name matches any of the given names, case-insensitive
+    pub fn matches_any_tag(&self, names: &[&str]) -> bool {
+        names.iter().any(|n| self.tag_eq_ignore_ascii_case(n))
+    }
+
+    /// The namespace prefix of the name before the first colon, like `svg` for `<svg:rect>`.
+    /// Returns `None` for names without a colon.
+    pub fn namespace(&self) -> Option<&str> {
+        self.name.split_once(':').map(|(namespace, _)| namespace)
+    }
+
+    /// The name without the namespace prefix, like `rect` for `<svg:rect>`. It's the whole name
+    /// for names without a colon.
+    pub fn local_name(&self) -> &str {
+        self.name
+            .split_once(':')
+            .map_or(&self.name, |(_, local_name)| local_name)
+    }
+
+    /// Check if the element has the class, surrounding whitespace of the name is ignored
+    pub fn has_class(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.classes.iter().any(|c| c == name)
+    }
+
+    /// Get the value of the `id` attribute, see `Element::get_attribute`
+    pub fn id(&self) -> Option<&str> {
+        self.get_attribute("id")
+    }
+
+    /// Get the value of the attribute with the key, case-insensitive. Returns `None` when the
+    /// attribute is missing or has no value, see `Element::has_attribute`. The classes aren't
+    /// part of the attributes, see `Element::classes`.
+    pub fn get_attribute(&self, key: &str) -> Option<&str> {
+        self.find_attribute(key).and_then(|value| value.as_deref())
+    }
+
+    /// Get all `data-*` attributes keyed by the name after `data-`, like `id` for `data-id`.
+    /// The value is `None` for attributes without value like `data-flag`.
+    pub fn dataset(&self) -> BTreeMap<&str, Option<&str>> {
+        self.attributes
+            .iter()
+            .filter_map(|(k, v)| {
+                let name = k
+                    .get(5..)
+                    .filter(|_| k[..5].eq_ignore_ascii_case("data-"))?;
+                Some((name, v.as_deref()))
+            })
+            .collect()
+    }
+
+    /// Get the value of the `data-*` attribute with the name after `data-`, see
+    /// `Element::get_attribute`. Ex: `data_attr("id")` for `data-id`
+    pub fn data_attr(&self, name: &str) -> Option<&str> {
+        self.get_attribute(&format!("data-{name}"))
+    }
+
+    /// Parse the declarations of the `style` attribute into property value pairs, like
+    /// `color: red; margin: 0`. The whitespace is trimmed and empty declarations or declarations
+    /// without value are skipped. The last declaration of a property wins. Semicolons within
+    /// quotes or parentheses, like in `url(data:image/png;base64,...)`, don't end the value.
+    pub fn style(&self) -> BTreeMap<&str, &str> {
+        let mut style = BTreeMap::new();
+        for declaration in split_declarations(self.get_attribute("style").unwrap_or_default()) {
+            if let Some((property, value)) = declaration.split_once(':') {
+                let (property, value) = (property.trim(), value.trim());
+                if !property.is_empty() && !value.is_empty() {
+                    style.insert(property, value);
+                }
+            }
+        }
+        style
+    }
+
+    /// Check if the element has the attribute with the key, case-insensitive, also when it has
+    /// no value like `disabled`
+    pub fn has_attribute(&self, key: &str) -> bool {
+        self.find_attribute(key).is_some()
+    }
+
+    fn find_attribute(&self, key: &str) -> Option<&Option<Cow<'s, str>>> {
+        self.attributes.get(key).or_else(|| {
+            self.attributes
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value)
+        })
+    }
+
+    /// Add the class, when the element doesn't have it yet. Surrounding whitespace is ignored
+    /// and an empty class isn't added.
+    pub fn add_class(&mut self, name: impl Into<Cow<'s, str>>) {
+        let name = match name.into() {
+            Cow::Borrowed(name) => Cow::Borrowed(name.trim()),
+            Cow::Owned(name) => Cow::Owned(name.trim().to_string()),
+        };
+        if !name.is_empty() && !self.has_class(&name) {
+            self.classes.push(name);
+        }
+    }
+
+    /// Remove all occurrences of the class, returns `true` when the element had the class
+    pub fn remove_class(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        let len = self.classes.len();
+        self.classes.retain(|c| c != name);
+        self.classes.len() != len
+    }
+
+    /// Set the value of the attribute. An existing attribute with the key in any case is
+    /// overwritten and keeps its position, otherwise the attribute is appended. The classes
+    /// aren't part of the attributes, see `Element::add_class`.
+    pub fn set_attribute(&mut self, key: impl Into<Cow<'s, str>>, value: impl Into<Cow<'s, str>>) {
+        let key = key.into();
+        let value = Some(value.into());
+        match self
+            .attributes
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+        {
+            Some((_, existing)) => *existing = value,
+            None => {
+                self.attributes.insert(key, value);
+            }
+        }
+    }
+
+    /// Remove the attribute with the key, case-insensitive. The order of the other attributes
+    /// is kept. Returns `true` when the element had the attribute.
+    pub fn remove_attribute(&mut self, key: &str) -> bool {
+        let len = self.attributes.len();
+        self.attributes.retain(|k, _| !k.eq_ignore_ascii_case(key));
+        self.attributes.len() != len
+    }
+
+    /// Find all descendants matching the css selector in document order, see `Dom::select`. The
+    /// element itself isn't part of the result, but it's known as ancestor, so `div > p` finds
+    /// the paragraphs of a `div` element. Ancestors outside of the element are unknown.
+    pub fn select(&self, selector: &str) -> crate::Result<Vec<&Element<'s>>> {
+        Ok(Selector::parse(selector)?.select(&self.children, vec![self]))
+    }
+
+    /// Check if the element is the root of foreign content, a `svg` or `math` element. Within
+    /// foreign content, elements use the xml rules, like self-closing elements without children.
+    pub fn is_foreign(&self) -> bool {
+        self.matches_any_tag(&["svg", "math"])
+    }
+
+    /// Check if the element is a void element, which can't have children, ex: `<br>`
+    pub fn is_void(&self) -> bool {
+        self.variant == ElementVariant::Void
+    }
+
+    /// Check if the element is a normal or raw text element without content, ex: `<span></span>`.
+    /// Children with only whitespace text count as empty, void elements are never empty elements.
+    pub fn is_empty_element(&self) -> bool {
+        self.variant != ElementVariant::Void
+            && self
+                .children
+                .iter()
+                .all(|c| matches!(c, Node::Text(t) if t.trim().is_empty()))
+    }
+
+    /// Convert the element into an owned element, which doesn't borrow from the source. Unlike
+    /// `Element::deep_clone_owned` the already owned strings aren't copied.
+    pub fn into_owned(self) -> Element<'static> {
+        Element {
+            name: intern::owned_name(self.name),
+            variant: self.variant,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = v.map(|v| Cow::Owned(v.into_owned()));
+                    (Cow::Owned(k.into_owned()), v)
+                })
+                .collect(),
+            classes: self
+                .classes
+                .into_iter()
+                .map(|c| Cow::Owned(c.into_owned()))
+                .collect(),
+            children: self.children.into_iter().map(Node::into_owned).collect(),
+            source_span: self.source_span.into_owned(),
+            depth: self.depth,
+        }
+    }
+
+    /// Clone the element into an owned element, which doesn't borrow from the source
+    pub fn deep_clone_owned(&self) -> Element<'static> {
+        Element {
+            name: intern::owned_name(Cow::Borrowed(&self.name)),
+            variant: self.variant.clone(),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|(k, v)| {
+                    let v = v.as_ref().map(|v| Cow::Owned(v.to_string()));
+                    (Cow::Owned(k.to_string()), v)
+                })
+                .collect(),
+            classes: self
+                .classes
+                .iter()
+                .map(|c| Cow::Owned(c.to_string()))
+                .collect(),
+            children: self.children.iter().map(Node::deep_clone_owned).collect(),
+            source_span: self.source_span.deep_clone_owned(),
+            depth: self.depth,
+        }
+    }
+
+    /// Insert the node as child at the index, shifting all children after it to the right.
+    /// Returns `Error::Manipulation` when the element is void or the index is out of bounds.
+    pub fn insert_child(&mut self, index: usize, node: Node<'s>) -> crate::Result<()> {
+        if self.variant == ElementVariant::Void {
+            return Err(crate::Error::Manipulation(format!(
+                "The void element {} can't have children",
+                self.name
+            )));
+        }
+        if index > self.children.len() {
+            return Err(crate::Error::Manipulation(format!(
+                "The index {} is out of bounds for {} children",
+                index,
+                self.children.len()
+            )));
+        }
+        self.children.insert(index, node);
+        Ok(())
+    }
+
+    /// Keep only the children for which the function returns `true`, the descendants of the
+    /// kept children are filtered as well. Ex: `retain_children(|n| n.element().is_none())`
+    pub fn retain_children<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Node) -> bool,
+    {
+        retain_nodes(&mut self.children, &mut f);
+    }
+
+    /// Parse the html and replace all children with the parsed nodes, which are owned and don't
+    /// borrow from the html, see `Node::parse_owned`. Returns `Error::Manipulation` when the
+    /// element is void and the parsing error for invalid html, the children are kept on errors.
+    pub fn set_inner_html(&mut self, html: &str) -> crate::Result<()> {
+        if self.variant == ElementVariant::Void {
+            return Err(crate::Error::Manipulation(format!(
+                "The void element {} can't have children",
+                self.name
+            )));
+        }
+        let mut children = Node::parse_owned(html)?;
+        shift_depths(&mut children, self.depth + 1);
+        self.children = children;
+        Ok(())
+    }
+
+    /// Merge adjacent text children into a single text and remove empty texts, the
+    /// descendants are normalized as well, like the dom `normalize()`
+    pub fn normalize(&mut self) {
+        normalize_nodes(&mut self.children);
+    }
+
+    /// Remove and return the child at the index, or `None` when the index is out of bounds
+    pub fn remove_child(&mut self, index: usize) -> Option<Node<'s>> {
+        (index < self.children.len()).then(|| self.children.remove(index))
+    }
+
+    /// Insert the node right before the first child matching the predicate.
+    /// Returns `true` when a matching child was found and the node was inserted.
+    pub fn insert_before<F>(&mut self, f: F, node: Node<'s>) -> bool
+    where
+        F: Fn(&Node) -> bool,
+    {
+        match self.children.iter().position(f) {
+            Some(index) => {
+                self.children.insert(index, node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert the node right after the first child matching the predicate.
+    /// Returns `true` when a matching child was found and the node was inserted.
+    pub fn insert_after<F>(&mut self, f: F, node: Node<'s>) -> bool
+    where
+        F: Fn(&Node) -> bool,
+    {
+        match self.children.iter().position(f) {
+            Some(index) => {
+                self.children.insert(index + 1, node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterate over all attributes as they would appear in the tag, including the `class`
+    /// attribute which is reconstituted from the classes. The class comes first and all other
+    /// attributes follow in alphabetical order, which is the same order used for the html output.
+    pub fn all_attributes(&self) -> impl Iterator<Item = (String, Option<String>)> + '_ {
+        let classes = self
+            .classes
+            .iter()
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        let class = match classes.is_empty() {
+            true => None,
+            false => Some((String::from("class"), Some(classes.join(" ")))),
+        };
+
+        let ordered_attributes: BTreeMap<_, _> = self.attributes.iter().collect();
+        class.into_iter().chain(
+            ordered_attributes
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.as_ref().map(|v| v.to_string()))),
+        )
+    }
+
+    /// Elements which are rendered on their own line by `rendered_text`
+    pub const BLOCK_ELEMENTS: &'static [&'static str] = &[
+        "address",
+        "article",
+        "aside",
+        "blockquote",
+        "br",
+        "dd",
+        "div",
+        "dl",
+        "dt",
+        "fieldset",
+        "figcaption",
+        "figure",
+        "footer",
+        "form",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "header",
+        "hr",
+        "li",
+        "main",
+        "nav",
+        "ol",
+        "p",
+        "pre",
+        "section",
+        "table",
+        "tr",
+        "ul",
+    ];
+
+    /// Get the human readable text of the element, close to how a browser would render it.
+    /// Text runs separated by an element boundary are joined with a single space, while
+    /// block-level elements (see `Element::BLOCK_ELEMENTS`) are separated by a new line.
+    /// Whitespace within the text is collapsed and the content of `script` and `style`
+    /// elements is skipped.
+    pub fn rendered_text(&self) -> String {
+        self.rendered_text_with(Self::BLOCK_ELEMENTS)
+    }
+
+    /// Same as `rendered_text` but with a custom set of block-level element names.
+    /// The names are compared case-insensitive.
+    pub fn rendered_text_with(&self, block_elements: &[&str]) -> String {
+        let mut text = String::new();
+        let mut new_line = false;
+        self.push_rendered_text(block_elements, &mut text, &mut new_line);
+        text
+    }
+
+    /// The text of all descendant text nodes in document order joined without any separator,
+    /// like the `textContent` of the browser dom. Comments are skipped, while the whitespace and
+    /// the content of `script` and `style` elements are kept, see `Element::rendered_text`.
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        self.push_text_content(false, &mut text, &mut false);
+        text
+    }
+
+    /// Same as `text_content`, but the text of block-level elements (see
+    /// `Element::BLOCK_ELEMENTS`) is separated by a single space, so `<p>a</p><p>b</p>` results
+    /// in `a b` instead of `ab`. No space is added when the text already has whitespace.
+    pub fn text_content_spaced(&self) -> String {
+        let mut text = String::new();
+        self.push_text_content(true, &mut text, &mut false);
+        text
+    }
+
+    pub(super) fn push_text_content(&self, spaced: bool, text: &mut String, space: &mut bool) {
+        for child in self.children.iter() {
+            child.push_text_content(spaced, text, space);
+        }
+    }
+
+    fn push_rendered_text(&self, block_elements: &[&str], text: &mut String, new_line: &mut bool) {
+        for child in self.children.iter() {
+            match child {
+                Node::Text(t) | Node::CData(t) => {
+                    let run = t.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if run.is_empty() {
+                        continue;
+                    }
+                    if !text.is_empty() {
+                        text.push(if *new_line { '\n' } else { ' ' });
+                    }
+                    text.push_str(&run);
+                    *new_line = false;
+                }
+                Node::Element(e) => {
+                    if e.name.eq_ignore_ascii_case("script") || e.name.eq_ignore_ascii_case("style")
+                    {
+                        continue;
+                    }
+                    let is_block = block_elements
+                        .iter()
+                        .any(|b| b.eq_ignore_ascii_case(&e.name));
+
+                    // block elements start and end with a new line
+                    *new_line |= is_block;
+                    e.push_rendered_text(block_elements, text, new_line);
+                    *new_line |= is_block;
+                }
+                Node::Comment(_)
+                | Node::ProcessingInstruction(_)
+                | Node::ConditionalComment { .. } => (),
+            }
+        }
+    }
 }
 
 impl<'s> Display for Element<'s> {
@@ -178,20 +925,96 @@ impl<'s> Display for Element<'s> {
     }
 }
 
+/// Elements are compared by their content, the `source_span` and `depth` are ignored. So the same
+/// markup parsed at different positions or a created element are equal.
+impl<'s> PartialEq for Element<'s> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.variant == other.variant
+            && self.attributes == other.attributes
+            && self.classes == other.classes
+            && self.children == other.children
+    }
+}
+
 impl<'s> Default for Element<'s> {
     fn default() -> Self {
         Self {
             name: Cow::Borrowed(""),
             variant: ElementVariant::Void,
             classes: vec![],
-            attributes: HashMap::new(),
+            attributes: IndexMap::new(),
             children: vec![],
             source_span: SourceSpan::default(),
+            depth: 0,
+        }
+    }
+}
+
+/// Split the css declarations at the semicolons, which aren't within quotes or parentheses
+fn split_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = vec![];
+    let mut start = 0;
+    let mut quote = None;
+    let mut parentheses = 0usize;
+    for (i, c) in style.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => (),
+            ('(', None) => parentheses += 1,
+            (')', None) => parentheses = parentheses.saturating_sub(1),
+            (';', None) if parentheses == 0 => {
+                declarations.push(&style[start..i]);
+                start = i + 1;
+            }
+            _ => (),
         }
     }
+    declarations.push(&style[start..]);
+    declarations
+}
+
+/// The quote for the attribute value, the other quote is used when the value only contains the
+/// preferred quote. A value with both quotes has the preferred quote encoded.
+fn attribute_quote(value: &str, preferred: char) -> char {
+    let other = if preferred == '"' { '\'' } else { '"' };
+    match value.contains(preferred) && !value.contains(other) {
+        true => other,
+        false => preferred,
+    }
 }
 
 fn ordered_map<S: Serializer>(value: &Attributes, serializer: S) -> Result<S::Ok, S::Error> {
     let ordered: BTreeMap<_, _> = value.iter().collect();
     ordered.serialize(serializer)
 }
+
+/// Borrow the name from the input when possible, otherwise known element names don't allocate
+fn deserialize_name<'de: 's, 's, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Cow<'s, str>, D::Error> {
+    struct NameVisitor;
+
+    impl<'de> Visitor<'de> for NameVisitor {
+        type Value = Cow<'de, str>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "an element name")
+        }
+
+        fn visit_borrowed_str<E>(self, name: &'de str) -> Result<Self::Value, E> {
+            Ok(Cow::Borrowed(name))
+        }
+
+        fn visit_str<E>(self, name: &str) -> Result<Self::Value, E> {
+            Ok(intern::owned_name(Cow::Borrowed(name)))
+        }
+
+        fn visit_string<E>(self, name: String) -> Result<Self::Value, E> {
+            Ok(intern::owned_name(Cow::Owned(name)))
+        }
+    }
+
+    deserializer.deserialize_str(NameVisitor)
+}
@@ -0,0 +1,87 @@
+use super::element::Attributes;
+use super::node::Node;
+use std::borrow::Cow;
+
+/// An event of the read-only event stream over the nodes, see `Dom::events`.
+/// Every start event is followed by the events of the children and the matching end event,
+/// also for void elements, which don't have children.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// The start of an element, the classes aren't part of the attributes like in `Element`
+    StartElement {
+        name: &'a str,
+        classes: &'a [Cow<'a, str>],
+        attributes: &'a Attributes<'a>,
+    },
+    /// The end of an element, after all of its children
+    EndElement { name: &'a str },
+    /// A text node
+    Text(&'a str),
+    /// A comment
+    Comment(&'a str),
+    /// The content of a CDATA section
+    CData(&'a str),
+    /// A processing instruction like the xml declaration, including `<?` and `?>`
+    ProcessingInstruction(&'a str),
+    /// The start of a conditional comment
+    StartConditionalComment { condition: &'a str },
+    /// The end of a conditional comment, after all of its children
+    EndConditionalComment,
+}
+
+/// Emits the events of the nodes in document order without recursion
+pub(super) struct Events<'a> {
+    // The siblings of each level down to the current node, with the index of the next sibling
+    // and the parent node, whose end event is emitted when all siblings are visited
+    index: Vec<(usize, &'a [Node<'a>], Option<&'a Node<'a>>)>,
+}
+
+impl<'a> Events<'a> {
+    /// Emit the events of the given nodes and all of their descendants
+    pub(super) fn new(nodes: &'a [Node<'a>]) -> Self {
+        Self {
+            index: vec![(0, nodes, None)],
+        }
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (next_sibling, siblings, parent) = self.index.last_mut()?;
+        let siblings: &'a [Node<'a>] = siblings;
+        let Some(node) = siblings.get(*next_sibling) else {
+            // all siblings are visited, end the parent
+            let parent = *parent;
+            self.index.pop();
+            return match parent {
+                Some(Node::Element(element)) => Some(Event::EndElement {
+                    name: &element.name,
+                }),
+                Some(_) => Some(Event::EndConditionalComment),
+                None => None,
+            };
+        };
+        *next_sibling += 1;
+
+        let event = match node {
+            Node::Element(element) => Event::StartElement {
+                name: &element.name,
+                classes: &element.classes,
+                attributes: &element.attributes,
+            },
+            Node::Text(text) => Event::Text(text),
+            Node::Comment(comment) => Event::Comment(comment),
+            Node::CData(data) => Event::CData(data),
+            Node::ProcessingInstruction(instruction) => Event::ProcessingInstruction(instruction),
+            Node::ConditionalComment { condition, .. } => {
+                Event::StartConditionalComment { condition }
+            }
+        };
+        if let Node::Element(_) | Node::ConditionalComment { .. } = node {
+            self.index.push((0, node.child_nodes(), Some(node)));
+        }
+        Some(event)
+    }
+}
@@ -1,11 +1,11 @@
 use crate::error::Error;
 use crate::Rule;
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, LineColLocation};
 
 /// This function abstracts the formatting of errors away from the core logic inside parser,
 /// so that the file is easier to read.
-pub fn error_msg<'s>(error: PestError<Rule>) -> Error {
-    let message = error.renamed_rules(|rule| match *rule {
+pub fn error_msg(error: PestError<Rule>) -> Error {
+    let error = error.renamed_rules(|rule| match *rule {
         Rule::EOI => "end of input".to_string(),
         Rule::doctype => "doctype element".to_string(),
         Rule::node_text => "text node".to_string(),
@@ -24,5 +24,64 @@ pub fn error_msg<'s>(error: PestError<Rule>) -> Error {
         // TODO: Continue with this
         x => format!("{:?} ", x),
     });
-    Error::Parsing(message.to_string())
+    let (line, column) = match error.line_col {
+        LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+    };
+    Error::ParsingAt {
+        message: error.variant.message().trim().to_string(),
+        line,
+        column,
+    }
+}
+
+/// A writer which stops the formatting with an error once the maximum amount of bytes is reached.
+/// Everything until the limit is kept in the buffer, cut at a valid char boundary.
+pub(crate) struct LimitedWriter {
+    pub buffer: String,
+    pub max_bytes: usize,
+    pub truncated: bool,
+}
+
+impl LimitedWriter {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            buffer: String::new(),
+            max_bytes,
+            truncated: false,
+        }
+    }
+}
+
+impl std::fmt::Write for LimitedWriter {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let remaining = self.max_bytes - self.buffer.len();
+        if s.len() <= remaining {
+            self.buffer.push_str(s);
+            return Ok(());
+        }
+
+        let mut end = remaining;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.buffer.push_str(&s[..end]);
+        self.truncated = true;
+        Err(std::fmt::Error)
+    }
+}
+
+/// Adapter to format into a `std::io::Write`. The io error is kept, because the formatting only
+/// returns a `std::fmt::Error` without details.
+pub(crate) struct IoWriter<W> {
+    pub inner: W,
+    pub error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
+    }
 }
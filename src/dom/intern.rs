@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+
+/// The names of the standard html elements and common svg elements, sorted for a binary search.
+/// Converting these names into owned names uses the static strings instead of allocating.
+#[rustfmt::skip]
+const ELEMENT_NAMES: &[&str] = &[
+    "a", "abbr", "acronym", "address", "applet", "area", "article", "aside", "audio", "b", "base",
+    "basefont", "bdi", "bdo", "big", "blink", "blockquote", "body", "br", "button", "canvas",
+    "caption", "center", "circle", "cite", "code", "col", "colgroup", "data", "datalist", "dd",
+    "defs", "del", "details", "dfn", "dialog", "dir", "div", "dl", "dt", "em", "embed", "fieldset",
+    "figcaption", "figure", "font", "footer", "form", "frame", "frameset", "g", "h1", "h2", "h3",
+    "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input",
+    "ins", "kbd", "label", "legend", "li", "line", "link", "main", "map", "mark", "marquee",
+    "math", "menu", "meta", "meter", "nav", "noframes", "noscript", "object", "ol", "optgroup",
+    "option", "output", "p", "param", "path", "picture", "polygon", "polyline", "pre", "progress",
+    "q", "rect", "rp", "rt", "ruby", "s", "samp", "script", "search", "section", "select", "slot",
+    "small", "source", "span", "strike", "strong", "style", "sub", "summary", "sup", "svg",
+    "table", "tbody", "td", "template", "textarea", "tfoot", "th", "thead", "time", "title", "tr",
+    "track", "tt", "u", "ul", "use", "var", "video", "wbr",
+];
+
+/// The names of `ELEMENT_NAMES` which are svg elements
+const SVG_ELEMENT_NAMES: &[&str] = &[
+    "circle", "defs", "g", "line", "path", "polygon", "polyline", "rect", "use",
+];
+
+/// Check if the name is a known html element, compared case-insensitive. Svg elements like
+/// `path` and custom elements are no html elements.
+pub(super) fn is_html_element(name: &str) -> bool {
+    static_name_lowercase(name).is_some_and(|name| !SVG_ELEMENT_NAMES.contains(&name))
+}
+
+/// Get the static string of a known element name, the name has to match exactly
+pub(super) fn static_name(name: &str) -> Option<&'static str> {
+    ELEMENT_NAMES
+        .binary_search(&name)
+        .ok()
+        .map(|i| ELEMENT_NAMES[i])
+}
+
+/// Get the lowercase static string of a known element name, compared case-insensitive
+pub(super) fn static_name_lowercase(name: &str) -> Option<&'static str> {
+    let lowercase = name.bytes().map(|b| b.to_ascii_lowercase());
+    ELEMENT_NAMES
+        .binary_search_by(|probe| probe.bytes().cmp(lowercase.clone()))
+        .ok()
+        .map(|i| ELEMENT_NAMES[i])
+}
+
+/// Convert the name into an owned name, known element names don't allocate
+pub(super) fn owned_name(name: Cow<'_, str>) -> Cow<'static, str> {
+    match static_name(&name) {
+        Some(name) => Cow::Borrowed(name),
+        None => Cow::Owned(name.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_names_are_sorted() {
+        assert!(ELEMENT_NAMES.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn it_finds_static_names() {
+        assert_eq!(static_name("div"), Some("div"));
+        assert_eq!(static_name("DIV"), None);
+        assert_eq!(static_name("x-icon"), None);
+        assert_eq!(static_name_lowercase("DiV"), Some("div"));
+        assert_eq!(static_name_lowercase("Ünicode"), None);
+        assert!(matches!(
+            owned_name(Cow::Borrowed("span")),
+            Cow::Borrowed("span")
+        ));
+        assert!(is_html_element("DIV"));
+        assert!(!is_html_element("path"));
+        assert!(!is_html_element("x-icon"));
+    }
+}
@@ -1,21 +1,64 @@
 use crate::Result;
-use options::FormattingOptions;
+use options::{FormattingOptions, ParseOptions};
 use pest::{iterators::Pairs, Parser};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::{default::Default, fmt::Display};
+use std::collections::{BTreeMap, BTreeSet};
+use std::{default::Default, fmt::Display, str::FromStr};
 
 use crate::error::Error;
 use crate::grammar::Grammar;
 use crate::Rule;
 
+pub mod builder;
 pub mod element;
+mod entities;
+pub mod event;
 pub mod formatting;
+mod intern;
 pub mod node;
 pub mod options;
+mod reparse;
+pub(crate) mod selector;
 pub mod span;
+pub mod visitor;
+pub mod warning;
 
-use node::Node;
+use element::{Attributes, Dir, Element};
+use entities::decode_char_reference;
+use event::{Event, Events};
+use node::{Node, NodeIntoIterator, UnclosedElements};
+use selector::Selector;
+use visitor::Visitor;
+use warning::Warning;
+
+/// Attributes which contain an url, which can execute scripts with a `javascript:` scheme
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "action", "formaction", "xlink:href"];
+
+/// Obsolete html elements with the reason and a suggested replacement
+/// See: https://html.spec.whatwg.org/multipage/obsolete.html#non-conforming-features
+const OBSOLETE_ELEMENTS: &[(&str, &str)] = &[
+    ("acronym", "obsolete, use <abbr> instead"),
+    ("applet", "obsolete, use <object> or <embed> instead"),
+    ("basefont", "obsolete, use css instead"),
+    ("big", "obsolete, use css font-size instead"),
+    ("blink", "obsolete, use css animations instead"),
+    ("center", "obsolete, use css text-align or margin instead"),
+    ("dir", "obsolete, use <ul> instead"),
+    ("font", "obsolete, use css instead"),
+    ("frame", "obsolete, use <iframe> or css layout instead"),
+    ("frameset", "obsolete, use <iframe> or css layout instead"),
+    ("isindex", "obsolete, use a <form> with an <input> instead"),
+    ("listing", "obsolete, use <pre> and <code> instead"),
+    ("marquee", "obsolete, use css animations instead"),
+    ("nobr", "obsolete, use css white-space instead"),
+    ("noframes", "obsolete, use <iframe> or css layout instead"),
+    ("plaintext", "obsolete, use <pre> instead"),
+    ("spacer", "obsolete, use css margin or padding instead"),
+    ("strike", "obsolete, use <del> or <s> instead"),
+    ("tt", "obsolete, use <code>, <kbd> or css instead"),
+    ("xmp", "obsolete, use <pre> and <code> instead"),
+];
 
 /// Document, DocumentFragment or Empty
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -50,19 +93,24 @@ pub struct Dom<'s> {
     /// The type of the tree that was parsed
     pub tree_type: DomVariant,
 
+    /// The doctype declaration as it's written in the source, like `<!DOCTYPE html>`
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    pub doctype: Option<Cow<'s, str>>,
+
     /// All of the root children in the tree
     #[serde(borrow, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<Node<'s>>,
 
     /// A collection of all warnings during parsing
-    #[serde(skip_serializing)]
-    pub warnings: Vec<String>,
+    #[serde(skip)]
+    pub warnings: Vec<Warning>,
 }
 
 impl<'s> Default for Dom<'s> {
     fn default() -> Self {
         Self {
             tree_type: DomVariant::Empty,
+            doctype: None,
             children: vec![],
             warnings: vec![],
         }
@@ -77,11 +125,63 @@ impl<'s> Dom<'s> {
 
     /// Parse a dom from a html string
     pub fn parse(input: &'s str) -> Result<Self> {
+        Self::parse_with(input, &ParseOptions::default())
+    }
+
+    /// Parse a dom from a html string with the given parse options
+    pub fn parse_with(input: &'s str, options: &ParseOptions) -> Result<Self> {
+        Self::parse_as(input, options, None)
+    }
+
+    /// Parse the dom from utf-8 encoded bytes, like the body of a network response. A leading
+    /// byte order mark is skipped and invalid utf-8 returns an `Error::Utf8`.
+    pub fn parse_bytes(input: &'s [u8]) -> Result<Self> {
+        let input = input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input);
+        Self::parse(std::str::from_utf8(input)?)
+    }
+
+    /// Parse a document fragment from a html string. Unlike `Dom::parse` the type isn't
+    /// detected from the content, so a fragment starting with `<html>` stays a fragment.
+    /// Returns an error for a doctype and for `head` or `body` elements at the top level.
+    pub fn parse_fragment(input: &'s str) -> Result<Self> {
+        Self::parse_fragment_with(input, &ParseOptions::default())
+    }
+
+    /// Parse a document fragment from a html string with the given parse options, see
+    /// `Dom::parse_fragment`
+    pub fn parse_fragment_with(input: &'s str, options: &ParseOptions) -> Result<Self> {
+        Self::parse_as(input, options, Some(DomVariant::DocumentFragment))
+    }
+
+    /// Parse a document from a html string. Unlike `Dom::parse` the type isn't detected from
+    /// the content. Returns an error when the top level doesn't consist of exactly one `html`
+    /// element, besides an optional doctype and comments.
+    pub fn parse_document(input: &'s str) -> Result<Self> {
+        Self::parse_document_with(input, &ParseOptions::default())
+    }
+
+    /// Parse a document from a html string with the given parse options, see
+    /// `Dom::parse_document`
+    pub fn parse_document_with(input: &'s str, options: &ParseOptions) -> Result<Self> {
+        Self::parse_as(input, options, Some(DomVariant::Document))
+    }
+
+    fn parse_as(
+        input: &'s str,
+        options: &ParseOptions,
+        tree_type: Option<DomVariant>,
+    ) -> Result<Self> {
         let pairs = match Grammar::parse(Rule::html, input) {
             Ok(pairs) => pairs,
             Err(error) => return Err(formatting::error_msg(error)),
         };
-        Self::build_dom(pairs)
+        node::check_depth(&pairs, options.max_depth)?;
+        let dom = Self::build_dom(pairs, options, tree_type)?;
+        node::check_strict(&dom.warnings, options)?;
+        if dom.tree_type == DomVariant::DocumentFragment {
+            node::check_single_root(&dom.children, options)?;
+        }
+        Ok(dom)
     }
 
     /// Create the dom from a json string
@@ -89,6 +189,36 @@ impl<'s> Dom<'s> {
         Ok(serde_json::from_str(json)?)
     }
 
+    /// Clone the dom into an owned dom, which doesn't borrow from the source anymore.
+    /// This is the same as cloning and converting into an owned dom, but without the
+    /// intermediate clone. An owned dom is `Send + Sync`, so it can be cached or shared with
+    /// other threads.
+    pub fn deep_clone_owned(&self) -> Dom<'static> {
+        Dom {
+            tree_type: self.tree_type.clone(),
+            doctype: self.doctype.as_ref().map(|d| Cow::Owned(d.to_string())),
+            children: self.children.iter().map(Node::deep_clone_owned).collect(),
+            warnings: self.warnings.clone(),
+        }
+    }
+
+    /// The warnings of the parsing as messages, like they were stored before `Warning` existed
+    pub fn warnings_as_strings(&self) -> Vec<String> {
+        self.warnings.iter().map(Warning::to_string).collect()
+    }
+
+    /// Convert the dom into an owned dom, which doesn't borrow from the source anymore, so the
+    /// source string can be dropped. Unlike `Dom::deep_clone_owned` the dom is consumed and the
+    /// already owned strings aren't copied.
+    pub fn into_owned(self) -> Dom<'static> {
+        Dom {
+            tree_type: self.tree_type,
+            doctype: self.doctype.map(|d| Cow::Owned(d.into_owned())),
+            children: self.children.into_iter().map(Node::into_owned).collect(),
+            warnings: self.warnings,
+        }
+    }
+
     /// Output the dom as a json formatted string
     pub fn to_json(&self) -> Result<String> {
         Ok(serde_json::to_string(self)?)
@@ -99,100 +229,490 @@ impl<'s> Dom<'s> {
         Ok(serde_json::to_string_pretty(self)?)
     }
 
-    /// Write the dom as a html string with the given formatting options
-    pub fn fmt_opt<W>(&self, f: &mut W, o: &FormattingOptions) -> std::fmt::Result
-    where
-        W: std::fmt::Write,
-    {
-        for child in self.children.iter() {
-            child.fmt_opt(f, o, 0)?;
-            write!(f, "\n")?;
+    /// Output the dom as a json string like `Dom::to_json`, where every element has a
+    /// `sourceSpan` with the `startLine`, `endLine`, `startColumn` and `endColumn` in the parsed
+    /// source. The text of the span isn't written and `Dom::parse_json` ignores the spans.
+    pub fn to_json_with_spans(&self) -> Result<String> {
+        let mut json = serde_json::to_value(self)?;
+        if let Some(children) = json.get_mut("children") {
+            add_source_spans(&self.children, children);
         }
-        Ok(())
+        Ok(json.to_string())
     }
 
-    fn build_dom(pairs: Pairs<'s, Rule>) -> Result<Self> {
-        let mut dom = Self::default();
+    /// Output the dom as a canonical json string, which is suited for hashing. All keys are
+    /// sorted and the whitespace is normalized like in `Dom::equals_ignoring_whitespace`, so
+    /// two doms which are equal ignoring whitespace produce the identical json. Source spans and
+    /// warnings are never part of the json.
+    pub fn to_canonical_json(&self) -> Result<String> {
+        let dom = Dom {
+            tree_type: self.tree_type.clone(),
+            doctype: self.doctype.clone(),
+            children: canonical_nodes(&self.children),
+            warnings: vec![],
+        };
+        let mut json = String::new();
+        write_canonical_json(&serde_json::to_value(&dom)?, &mut json);
+        Ok(json)
+    }
 
-        // NOTE: The logic is roughly as follows:
-        // 1) A document containing nothing but comments is DomVariant::Empty even though it will have
-        //    children in this first pass.  We fix this in the next section.  This allows us to use
-        //    DomVariant::Empty to indicate "we haven't decided the type yet".
-        // 2) If the type is DomVariant::Empty _so far_, then it can be changed to DomVariant::Document
-        //    or DomVariant::DocumentFragment.  DomVariant is only selected in this stage if we see a
-        //    DOCTYPE tag.  Comments do not change the type.
-        // 3) If the type is non-empty, we don't re-set the type.  We do look for conflicts between
-        //    the type and the tokens in the next stage.
-        for pair in pairs {
-            match pair.as_rule() {
-                // A <!DOCTYPE> tag means a full-fledged document.  Note that because of the way
-                // the grammar is written, we will only get this token if the <!DOCTYPE> occurs
-                // before any other tag; otherwise it will be parsed as a custom tag.
-                Rule::doctype => {
-                    if dom.tree_type == DomVariant::Empty {
-                        dom.tree_type = DomVariant::Document;
-                    }
+    /// Compare two doms while ignoring insignificant whitespace.
+    ///
+    /// The following differences are ignored:
+    /// - Leading, trailing and repeated whitespace within text nodes and comments
+    /// - Text nodes which only contain whitespace
+    /// - The order of the attributes
+    /// - The source spans and the warnings
+    ///
+    /// Element names, attribute values, the order of the classes and the tree type are still
+    /// compared exactly.
+    pub fn equals_ignoring_whitespace(&self, other: &Dom) -> bool {
+        self.tree_type == other.tree_type
+            && self.doctype == other.doctype
+            && nodes_equal(
+                &self.children,
+                &other.children,
+                Equality::IgnoringWhitespace,
+            )
+    }
+
+    /// Compare two doms by their meaning, like for round trip assertions in tests.
+    ///
+    /// The following differences are ignored:
+    /// - Leading and trailing whitespace of text nodes and comments
+    /// - Text nodes which only contain whitespace
+    /// - The order of the classes and the attributes
+    /// - The source spans and the warnings
+    ///
+    /// Unlike `Dom::equals_ignoring_whitespace` the whitespace within text is compared exactly.
+    pub fn semantically_eq(&self, other: &Dom) -> bool {
+        self.tree_type == other.tree_type
+            && self.doctype == other.doctype
+            && nodes_equal(&self.children, &other.children, Equality::Semantic)
+    }
+
+    /// Count the words of the visible text, the content of `script` and `style` elements is
+    /// skipped. Words are separated by whitespace or element boundaries, see
+    /// `Element::rendered_text`.
+    pub fn word_count(&self) -> usize {
+        self.children
+            .iter()
+            .map(|node| match node {
+                Node::Text(t) => t.split_whitespace().count(),
+                Node::Element(e)
+                    if !e.name.eq_ignore_ascii_case("script")
+                        && !e.name.eq_ignore_ascii_case("style") =>
+                {
+                    e.rendered_text().split_whitespace().count()
                 }
+                _ => 0,
+            })
+            .sum()
+    }
 
-                // If we see an element, build the sub-tree and add it as a child.  If we don't
-                // have a document type yet (i.e. "empty"), select DocumentFragment
-                Rule::node_element => match Node::build_node_element(pair, &mut dom.warnings) {
-                    Ok(el) => {
-                        if let Some(node) = el {
-                            if dom.tree_type == DomVariant::Empty {
-                                dom.tree_type = DomVariant::DocumentFragment;
-                            };
-                            dom.children.push(node);
-                        }
-                    }
-                    Err(error) => {
-                        dom.warnings.push(format!("{}", error));
-                    }
-                },
+    /// Estimate the minutes needed to read the visible text with the given words per minute.
+    /// Returns `0.0` when the words per minute are zero.
+    pub fn reading_time_minutes(&self, wpm: usize) -> f64 {
+        if wpm == 0 {
+            return 0.0;
+        }
+        self.word_count() as f64 / wpm as f64
+    }
 
-                // Similar to an element, we add it as a child and select DocumentFragment if we
-                // don't already have a document type.
-                Rule::node_text => {
-                    if dom.tree_type == DomVariant::Empty {
-                        dom.tree_type = DomVariant::DocumentFragment;
-                    }
-                    let text = pair.as_str();
-                    if !text.trim().is_empty() {
-                        dom.children.push(Node::Text(Cow::Borrowed(text)));
+    /// Remove the url attributes `href`, `src`, `action`, `formaction` and `xlink:href`, when
+    /// the scheme of the url isn't one of the allowed schemes, ex: `&["http", "https"]`. This
+    /// neutralizes urls like `javascript:alert(1)`. Relative urls without a scheme are kept.
+    /// Like browsers, whitespace, control characters and character references within the scheme
+    /// are respected, so `java&#x09;script:` is detected as `javascript`.
+    pub fn sanitize_urls(&mut self, allowed_schemes: &[&str]) {
+        for_each_element_mut(&mut self.children, &mut |element| {
+            element.attributes.retain(|key, value| {
+                let scheme = match value {
+                    Some(url) if URL_ATTRIBUTES.iter().any(|a| key.eq_ignore_ascii_case(a)) => {
+                        url_scheme(url)
                     }
+                    _ => None,
+                };
+                scheme.map_or(true, |scheme| {
+                    allowed_schemes
+                        .iter()
+                        .any(|s| s.eq_ignore_ascii_case(&scheme))
+                })
+            });
+        });
+    }
+
+    /// Walk over all nodes of the dom in document order with the visitor, the root nodes have
+    /// the depth `0`, see `Visitor`
+    pub fn walk<V>(&self, visitor: &mut V)
+    where
+        V: Visitor + ?Sized,
+    {
+        visitor::walk_nodes(&self.children, visitor, 0);
+    }
+
+    /// Keep only the nodes for which the function returns `true` in the whole dom, see
+    /// `Element::retain_children`
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Node) -> bool,
+    {
+        node::retain_nodes(&mut self.children, &mut f);
+    }
+
+    /// Merge adjacent text nodes into a single text and remove empty texts in the whole dom,
+    /// see `Element::normalize`
+    pub fn normalize(&mut self) {
+        node::normalize_nodes(&mut self.children);
+    }
+
+    /// Remove all text nodes which contain only whitespace, except within `pre` and `textarea`
+    /// elements. This also removes the whitespace between inline elements, like the space in
+    /// `<b>a</b> <i>b</i>`.
+    pub fn remove_whitespace_only_text(&mut self) {
+        remove_whitespace_only_text(&mut self.children);
+    }
+
+    /// Find all elements matching the css selector in document order. Supported are tag names,
+    /// `*`, `#id`, `.class`, `[attr]` and `[attr=value]`, combined into compound selectors like
+    /// `a.btn[href]`, the descendant (`div p`) and child (`div > p`) combinators and selector
+    /// lists like `h1, h2`. Tag names and attribute keys are compared case-insensitive.
+    /// Namespaced names like `svg:rect` are written as `svg|rect` or `svg\:rect`.
+    /// Returns an error for an invalid selector or an unsupported pseudo-class like `a:hover`.
+    pub fn select(&self, selector: &str) -> Result<Vec<&Element<'s>>> {
+        Ok(Selector::parse(selector)?.select(&self.children, vec![]))
+    }
+
+    /// Remove everything except the elements matching the css selector (see `Dom::select`),
+    /// together with their descendants. The ancestors of the matching elements are kept as
+    /// wrappers to preserve the structure, but all their other children are removed.
+    /// Returns an error for an invalid selector.
+    pub fn prune_to(&mut self, selector: &str) -> Result<()> {
+        let paths = Selector::parse(selector)?.match_paths(&self.children);
+        prune_nodes(&mut self.children, &paths, &mut vec![]);
+        Ok(())
+    }
+
+    /// Find all obsolete html elements like `<center>` or `<font>`, together with a short
+    /// reason and the suggested replacement. The dom isn't modified.
+    pub fn obsolete_elements(&self) -> Vec<(&Element<'_>, &'static str)> {
+        self.descendant_elements()
+            .filter_map(|e| {
+                OBSOLETE_ELEMENTS
+                    .iter()
+                    .find(|(name, _)| e.name.eq_ignore_ascii_case(name))
+                    .map(|(_, reason)| (e, *reason))
+            })
+            .collect()
+    }
+
+    /// Find all attributes in the dom which match the given predicate, called with the key and
+    /// value of each attribute. The matches are returned together with their element in
+    /// document order, attributes of the same element in alphabetical order. The classes
+    /// aren't part of the attributes, see `Element::classes`.
+    pub fn attributes_matching<F>(&self, f: F) -> Vec<(&Element<'_>, &str, Option<&str>)>
+    where
+        F: Fn(&str, Option<&str>) -> bool,
+    {
+        let mut matches = vec![];
+        for element in self.descendant_elements() {
+            let ordered_attributes: BTreeMap<_, _> = element.attributes.iter().collect();
+            for (key, value) in ordered_attributes {
+                if f(key, value.as_deref()) {
+                    matches.push((element, key.as_ref(), value.as_deref()));
                 }
+            }
+        }
+        matches
+    }
 
-                // Store comments as a child, but it doesn't affect the document type selection
-                // until the next phase (validation).
-                Rule::node_comment => {
-                    dom.children
-                        .push(Node::Comment(Cow::Borrowed(pair.into_inner().as_str())));
+    /// Find all elements in document order which have the attribute. With a value only elements
+    /// with exactly this value match, otherwise every element with the attribute. The key is
+    /// compared case-insensitive and the value case-sensitive, see
+    /// `Dom::elements_with_attr_ignore_case`. The key `class` matches the joined classes.
+    pub fn elements_with_attr(&self, key: &str, value: Option<&str>) -> Vec<&Element<'_>> {
+        self.elements_with_attr_by(key, value, |a, b| a == b)
+    }
+
+    /// Like `Dom::elements_with_attr`, but the value is compared case-insensitive as well
+    pub fn elements_with_attr_ignore_case(
+        &self,
+        key: &str,
+        value: Option<&str>,
+    ) -> Vec<&Element<'_>> {
+        self.elements_with_attr_by(key, value, |a, b| a.eq_ignore_ascii_case(b))
+    }
+
+    fn elements_with_attr_by<F>(&self, key: &str, value: Option<&str>, eq: F) -> Vec<&Element<'_>>
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        self.descendant_elements()
+            .filter(|e| {
+                let attribute = match key.eq_ignore_ascii_case("class") {
+                    true => (!e.classes.is_empty()).then(|| Some(e.classes.join(" ").into())),
+                    false => e
+                        .attributes
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                        .map(|(_, v)| v.clone()),
+                };
+                match (attribute, value) {
+                    (Some(_), None) => true,
+                    (Some(Some(attribute)), Some(value)) => eq(&attribute, value),
+                    _ => false,
                 }
+            })
+            .collect()
+    }
 
-                // Ignore 'end of input', which then allows the catch-all unreachable!() arm to
-                // function properly.
-                Rule::EOI => (),
+    /// Find the first element in document order for which the function returns `true`
+    pub fn find_first<F>(&self, f: F) -> Option<&Element<'_>>
+    where
+        F: Fn(&Element) -> bool,
+    {
+        self.descendant_elements().find(|e| f(e))
+    }
 
-                // This should be unreachable, due to the way the grammar is written
-                _ => unreachable!("[build dom] unknown rule: {:?}", pair.as_rule()),
+    /// Find all elements in document order for which the function returns `true`
+    pub fn find_all<F>(&self, f: F) -> Vec<&Element<'_>>
+    where
+        F: Fn(&Element) -> bool,
+    {
+        self.descendant_elements().filter(|e| f(e)).collect()
+    }
+
+    /// Get all elements with the tag name in document order, case-insensitive
+    pub fn get_elements_by_tag_name(&self, name: &str) -> Vec<&Element<'_>> {
+        self.find_all(|e| e.tag_eq_ignore_ascii_case(name))
+    }
+
+    /// Get all elements with the class in document order, see `Element::has_class`
+    pub fn get_elements_by_class_name(&self, class: &str) -> Vec<&Element<'_>> {
+        self.find_all(|e| e.has_class(class))
+    }
+
+    /// Get the node at the index path, starting at the root nodes. Ex: `[1, 0]` is the first
+    /// child of the second root node. The paths are yielded by `NodeIntoIterator::with_paths`.
+    pub fn node_at(&self, path: &[usize]) -> Option<&Node<'_>> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.children.get(*first)?;
+        for index in rest {
+            node = node.child_nodes().get(*index)?;
+        }
+        Some(node)
+    }
+
+    /// Get the parent element of the node at the index path, `None` for root nodes and nodes
+    /// within a conditional comment
+    pub fn parent_of(&self, path: &[usize]) -> Option<&Element<'_>> {
+        let (_, parent) = path.split_last()?;
+        self.node_at(parent)?.element()
+    }
+
+    /// Get the next sibling of the node at the index path
+    pub fn next_sibling(&self, path: &[usize]) -> Option<&Node<'_>> {
+        let (index, parent) = path.split_last()?;
+        let siblings = match parent.is_empty() {
+            true => &self.children,
+            false => self.node_at(parent)?.child_nodes(),
+        };
+        siblings.get(index + 1)
+    }
+
+    /// The text direction of the node at the index path, from the nearest `dir` attribute of the
+    /// node or its ancestors. Invalid values are skipped, the default is `Dir::Ltr`.
+    pub fn effective_dir(&self, path: &[usize]) -> Dir {
+        self.inherited_attribute(path, "dir", Dir::parse)
+            .unwrap_or_default()
+    }
+
+    /// The language of the node at the index path, from the nearest `lang` attribute of the node
+    /// or its ancestors. An empty value means the language is unknown.
+    pub fn effective_lang(&self, path: &[usize]) -> Option<&str> {
+        self.inherited_attribute(path, "lang", Some)
+    }
+
+    /// Check if the content of the node at the index path should be translated, from the
+    /// nearest `translate` attribute of the node or its ancestors. Only `translate="no"`
+    /// disables the translation, invalid values are skipped.
+    pub fn effective_translate(&self, path: &[usize]) -> bool {
+        self.inherited_attribute(path, "translate", |value| {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "" | "yes" => Some(true),
+                "no" => Some(false),
+                _ => None,
+            }
+        })
+        .unwrap_or(true)
+    }
+
+    /// Find the first valid value of the attribute on the node at the path and its ancestors,
+    /// an attribute without value is empty
+    fn inherited_attribute<'a, T, F>(&'a self, path: &[usize], key: &str, f: F) -> Option<T>
+    where
+        F: Fn(&'a str) -> Option<T>,
+    {
+        (1..=path.len())
+            .rev()
+            .filter_map(|len| self.node_at(&path[..len])?.element())
+            .filter(|e| e.has_attribute(key))
+            .map(|e| e.get_attribute(key).unwrap_or_default())
+            .find_map(f)
+    }
+
+    /// Replace the node at the index path with the replacement nodes, which take its position
+    /// in the children of the parent. Ex: replace a `<b>` found by `NodeIntoIterator::with_paths`
+    /// with a `<strong>`. Returns an error when there is no node at the path.
+    pub fn replace_with(&mut self, path: &[usize], replacement: Vec<Node<'s>>) -> Result<()> {
+        let missing = || Error::Manipulation(format!("There is no node at the path {:?}", path));
+        let (index, parent) = path.split_last().ok_or_else(missing)?;
+        if self.node_at(path).is_none() {
+            return Err(missing());
+        }
+
+        let mut siblings = &mut self.children;
+        for i in parent {
+            siblings = match &mut siblings[*i] {
+                Node::Element(e) => &mut e.children,
+                Node::ConditionalComment { children, .. } => children,
+                _ => return Err(missing()),
             };
         }
+        siblings.splice(*index..=*index, replacement);
+        Ok(())
+    }
 
-        // Implement some checks on the generated dom's data and initial type.  The type may be
-        // modified in this section.
-        match dom.tree_type {
+    /// Iterate over the start and end events of all nodes in document order without recursion,
+    /// like a SAX parser. The doctype isn't part of the events, see `Dom::doctype`
+    pub fn events(&self) -> impl Iterator<Item = Event<'_>> {
+        Events::new(&self.children)
+    }
+
+    /// Iterate over all elements of the dom in document order, text and comments are skipped
+    pub fn descendant_elements(&self) -> impl Iterator<Item = &Element<'_>> {
+        self.into_iter().filter_map(Node::element)
+    }
+
+    /// Write the dom as a html string with the given formatting options. The characters `<`, `>`
+    /// and `&` in text and attribute values are written as character references, except in
+    /// `script` and `style` elements. Every `&` is encoded, so the references of text which
+    /// was parsed with `ParseOptions::decode_entities` disabled are encoded again.
+    /// The doctype is written first, before the comments of the top level, only a leading xml
+    /// declaration is written before the doctype.
+    pub fn fmt_opt<W>(&self, f: &mut W, o: &FormattingOptions) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        let declarations = self
+            .children
+            .iter()
+            .take_while(|n| matches!(n, Node::ProcessingInstruction(_)))
+            .count();
+        let (declarations, children) = self.children.split_at(declarations);
+        for declaration in declarations {
+            declaration.fmt_opt(f, o, 0)?;
+            if o.new_lines {
+                o.fmt_new_line(f)?;
+            }
+        }
+        if let Some(doctype) = &self.doctype {
+            write!(f, "{}", doctype)?;
+            if o.new_lines {
+                o.fmt_new_line(f)?;
+            }
+        }
+        for child in children.iter() {
+            child.fmt_opt(f, o, 0)?;
+            if o.new_lines {
+                o.fmt_new_line(f)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the dom as html into the writer without building a `String` first, see
+    /// `Dom::fmt_opt`
+    pub fn write_html<W>(&self, w: &mut W, o: &FormattingOptions) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        self.fmt_opt(w, o)
+    }
+
+    /// Write the dom as html into the io writer, like a file or socket, see `Dom::write_html`.
+    /// The writer isn't buffered or flushed, wrap it in a `std::io::BufWriter` for many small
+    /// writes.
+    pub fn write_html_io<W>(&self, w: W, o: &FormattingOptions) -> Result<()>
+    where
+        W: std::io::Write,
+    {
+        let mut writer = formatting::IoWriter {
+            inner: w,
+            error: None,
+        };
+        match self.fmt_opt(&mut writer, o) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(match writer.error {
+                Some(error) => Error::IO(error),
+                None => Error::IO(std::io::Error::new(std::io::ErrorKind::Other, error)),
+            }),
+        }
+    }
+
+    /// Output the dom as a html string which is at most `max_bytes` long. When the output
+    /// doesn't fit, the formatting stops early and the marker `...` is appended, which isn't
+    /// counted against the limit.
+    pub fn to_html_truncated(&self, o: &FormattingOptions, max_bytes: usize) -> String {
+        let mut writer = formatting::LimitedWriter::new(max_bytes);
+        if self.fmt_opt(&mut writer, o).is_err() && writer.truncated {
+            writer.buffer.push_str("...");
+        }
+        writer.buffer
+    }
+
+    /// Check the structure after manual changes of the children and detect the type again,
+    /// with the same rules as when parsing. A document has only one `html` element at the top
+    /// level and a document fragment doesn't include `head` or `body` at the top level.
+    /// A dom with a doctype or with only an `html` element is a document.
+    pub fn validate(&mut self) -> Result<()> {
+        // The initial type is selected by the doctype or the first content at the top level,
+        // comments and conditional comments don't change the type
+        self.tree_type = if self.doctype.is_some() {
+            DomVariant::Document
+        } else if self
+            .children
+            .iter()
+            .any(|n| matches!(n, Node::Element(_) | Node::Text(_) | Node::CData(_)))
+        {
+            DomVariant::DocumentFragment
+        } else {
+            DomVariant::Empty
+        };
+        self.check_tree_type()
+    }
+
+    /// Implement some checks on the dom's data and initial type, the type may be modified
+    fn check_tree_type(&mut self) -> Result<()> {
+        match self.tree_type {
             // A DomVariant::Empty can only have comments. Anything else is an error.
             DomVariant::Empty => {
-                for node in &dom.children {
-                    if let Node::Comment(_) = node {
+                for node in &self.children {
+                    if let Node::Comment(_)
+                    | Node::ProcessingInstruction(_)
+                    | Node::ConditionalComment { .. } = node
+                    {
                         // An "empty" document, but it has comments - this is where we cleanup the
                         // earlier assumption that a document with only comments is "empty".
                         // Really, it is a "fragment".
-                        dom.tree_type = DomVariant::DocumentFragment
+                        self.tree_type = DomVariant::DocumentFragment
                     } else {
                         // Anything else (i.e. Text() or Element() ) can't happen at the top level;
                         // if we had seen one, we would have set the document type above
-                        unreachable!("[build dom] empty document with an Element {:?}", node)
+                        return Err(Error::Parsing(format!(
+                            "[build dom] empty document with an Element {:?}",
+                            node
+                        )));
                     }
                 }
             }
@@ -200,17 +720,18 @@ impl<'s> Dom<'s> {
             // A DomVariant::Document can only have comments and an <HTML> node at the top level.
             // Only one <HTML> tag is permitted.
             DomVariant::Document => {
-                if dom
+                if self
                     .children
                     .iter()
-                    .filter(|x| match x {
-                        Node::Element(el) if el.name.to_lowercase() == "html" => true,
-                        _ => false,
-                    })
+                    .filter(
+                        |x| matches!(x, Node::Element(el) if el.tag_eq_ignore_ascii_case("html")),
+                    )
                     .count()
                     > 1
                 {
-                    return Err(Error::Parsing(format!("Document with multiple HTML tags",)));
+                    return Err(Error::Parsing(
+                        "Document with multiple HTML tags".to_string(),
+                    ));
                 }
             }
 
@@ -221,11 +742,13 @@ impl<'s> Dom<'s> {
                 let mut seen_html = false;
                 let mut seen_elements = false;
 
-                for node in &dom.children {
+                for node in &self.children {
                     match node {
                         // Nodes other than <HTML> - reject <HEAD> and <BODY>
-                        Node::Element(ref el) if el.name.to_lowercase() != "html" => {
-                            if el.name == "head" || el.name == "body" {
+                        Node::Element(ref el) if !el.tag_eq_ignore_ascii_case("html") => {
+                            if el.tag_eq_ignore_ascii_case("head")
+                                || el.tag_eq_ignore_ascii_case("body")
+                            {
                                 return Err(Error::Parsing(format!(
                                     "A document fragment should not include {}",
                                     el.name
@@ -234,7 +757,7 @@ impl<'s> Dom<'s> {
                             seen_elements = true;
                         }
                         // <HTML> Nodes - one (before any other elements) is okay
-                        Node::Element(ref el) if el.name.to_lowercase() == "html" => {
+                        Node::Element(ref el) if el.tag_eq_ignore_ascii_case("html") => {
                             if seen_html || seen_elements {
                                 return Err(Error::Parsing(format!(
                                     "A document fragment should not include {}",
@@ -243,7 +766,7 @@ impl<'s> Dom<'s> {
                             };
 
                             // A fragment with just an <HTML> tag is a document
-                            dom.tree_type = DomVariant::Document;
+                            self.tree_type = DomVariant::Document;
                             seen_html = true;
                         }
                         // Comment() and Text() nodes are permitted at the top-level of a
@@ -254,13 +777,458 @@ impl<'s> Dom<'s> {
             }
         }
 
+        Ok(())
+    }
+
+    /// Build the dom, the type is detected from the content when no `tree_type` is given
+    fn build_dom(
+        pairs: Pairs<'s, Rule>,
+        options: &ParseOptions,
+        tree_type: Option<DomVariant>,
+    ) -> Result<Self> {
+        let mut dom = Self::default();
+        let mut unclosed = UnclosedElements::default();
+
+        // NOTE: The logic is roughly as follows:
+        // 1) A document containing nothing but comments is DomVariant::Empty even though it will have
+        //    children in this first pass.  We fix this in the next section.  This allows us to use
+        //    DomVariant::Empty to indicate "we haven't decided the type yet".
+        // 2) If the type is DomVariant::Empty _so far_, then it can be changed to DomVariant::Document
+        //    or DomVariant::DocumentFragment.  DomVariant is only selected in this stage if we see a
+        //    DOCTYPE tag.  Comments do not change the type.
+        // 3) If the type is non-empty, we don't re-set the type.  We do look for conflicts between
+        //    the type and the tokens in the next stage.
+        for pair in pairs {
+            match pair.as_rule() {
+                // A <!DOCTYPE> tag means a full-fledged document.  Note that because of the way
+                // the grammar is written, we will only get this token if the <!DOCTYPE> occurs
+                // before any other tag; otherwise it will be parsed as a custom tag.
+                Rule::doctype => {
+                    if tree_type == Some(DomVariant::DocumentFragment) {
+                        return Err(Error::Parsing(
+                            "A document fragment should not include a doctype".to_string(),
+                        ));
+                    }
+                    dom.doctype = Some(Cow::Borrowed(pair.as_str().trim()));
+                    if dom.tree_type == DomVariant::Empty {
+                        dom.tree_type = DomVariant::Document;
+                    }
+                }
+
+                // If we see an element, build the sub-tree and add it as a child.  If we don't
+                // have a document type yet (i.e. "empty"), select DocumentFragment
+                Rule::node_element => {
+                    let (line, column) = pair.line_col();
+                    let start = UnclosedElements::start(&pair);
+                    match Node::build_node_element(
+                        pair,
+                        options,
+                        &mut dom.warnings,
+                        false,
+                        false,
+                        0,
+                    ) {
+                        Ok(el) => {
+                            unclosed.push(start, &el, dom.children.len());
+                            if let Some(node) = el {
+                                if dom.tree_type == DomVariant::Empty {
+                                    dom.tree_type = DomVariant::DocumentFragment;
+                                };
+                                node::push_node(&mut dom.children, node);
+                            }
+                        }
+                        Err(error) => {
+                            dom.warnings.push(Warning::parse_error(error, line, column));
+                        }
+                    }
+                }
+
+                // Similar to an element, we add it as a child and select DocumentFragment if we
+                // don't already have a document type.
+                Rule::node_text => {
+                    if dom.tree_type == DomVariant::Empty {
+                        dom.tree_type = DomVariant::DocumentFragment;
+                    }
+                    let text = pair.as_str();
+                    if !text.trim().is_empty() {
+                        node::push_node(
+                            &mut dom.children,
+                            Node::Text(node::build_node_text(text, options, false)),
+                        );
+                    }
+                }
+
+                // Store comments as a child, but it doesn't affect the document type selection
+                // until the next phase (validation).
+                Rule::node_comment => {
+                    dom.children.extend(Node::build_node_comment(
+                        pair,
+                        options,
+                        &mut dom.warnings,
+                        0,
+                    ));
+                }
+
+                // The xml declaration doesn't affect the document type selection, like comments
+                Rule::node_process_instruct => {
+                    dom.children.push(node::build_node_process_instruct(pair));
+                }
+
+                // A CDATA section is content like text and selects DocumentFragment as well
+                Rule::node_cdata => {
+                    if dom.tree_type == DomVariant::Empty {
+                        dom.tree_type = DomVariant::DocumentFragment;
+                    }
+                    dom.children.push(node::build_node_cdata(pair));
+                }
+
+                // The 'end of input' ends the content of unclosed elements, it's matched to
+                // allow the catch-all error arm to function properly.
+                Rule::EOI => unclosed.end(&pair),
+
+                // This should be unreachable, due to the way the grammar is written
+                _ => {
+                    return Err(Error::Parsing(format!(
+                        "[build dom] unknown rule: {:?}",
+                        pair.as_rule()
+                    )))
+                }
+            };
+        }
+        unclosed.nest(&mut dom.children, options, false)?;
+
+        // A given type isn't detected, the content is only validated against it
+        match tree_type {
+            Some(DomVariant::DocumentFragment) => {
+                let head_or_body = dom.children.iter().find_map(|node| match node {
+                    Node::Element(el) if el.matches_any_tag(&["head", "body"]) => Some(el),
+                    _ => None,
+                });
+                if let Some(el) = head_or_body {
+                    return Err(Error::Parsing(format!(
+                        "A document fragment should not include {}",
+                        el.name
+                    )));
+                }
+                dom.tree_type = DomVariant::DocumentFragment;
+                return Ok(dom);
+            }
+            Some(DomVariant::Document) => {
+                let only_html = dom.children.iter().all(|node| match node {
+                    Node::Element(el) => el.matches_any_tag(&["html"]),
+                    Node::Comment(_)
+                    | Node::ProcessingInstruction(_)
+                    | Node::ConditionalComment { .. } => true,
+                    Node::Text(_) | Node::CData(_) => false,
+                });
+                let elements = dom.children.iter().filter(|n| n.element().is_some());
+                if !only_html || elements.count() != 1 {
+                    return Err(Error::Parsing(
+                        "A document should only have one html element at the top level".to_string(),
+                    ));
+                }
+                dom.tree_type = DomVariant::Document;
+                return Ok(dom);
+            }
+            _ => (),
+        }
+
         // The result is the validated tree
+        dom.check_tree_type()?;
         Ok(dom)
     }
 }
 
+/// Add the source spans of the elements to their json values
+fn add_source_spans(nodes: &[Node], json: &mut serde_json::Value) {
+    let values = match json.as_array_mut() {
+        Some(values) => values,
+        None => return,
+    };
+    for (node, value) in nodes.iter().zip(values) {
+        if let Node::Element(e) = node {
+            let span = &e.source_span;
+            value["sourceSpan"] = serde_json::json!({
+                "startLine": span.start_line,
+                "endLine": span.end_line,
+                "startColumn": span.start_column,
+                "endColumn": span.end_column,
+            });
+        }
+        if let Some(children) = value.get_mut("children") {
+            add_source_spans(node.child_nodes(), children);
+        }
+    }
+}
+
+fn remove_whitespace_only_text(nodes: &mut Vec<Node>) {
+    nodes.retain(|node| !matches!(node, Node::Text(t) if t.trim().is_empty()));
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Element(e) if !e.matches_any_tag(&["pre", "textarea"]) => {
+                remove_whitespace_only_text(&mut e.children)
+            }
+            Node::ConditionalComment { children, .. } => remove_whitespace_only_text(children),
+            _ => (),
+        }
+    }
+}
+
+/// Call the function for every element and its descendants
+fn for_each_element_mut<F>(nodes: &mut [Node], f: &mut F)
+where
+    F: FnMut(&mut Element),
+{
+    for node in nodes.iter_mut() {
+        if let Node::Element(e) = node {
+            f(e);
+        }
+        for_each_element_mut(node.child_nodes_mut(), f);
+    }
+}
+
+/// The lowercase scheme of the url, like `javascript` for `JavaScript:alert(1)`, or `None` for
+/// relative urls. Whitespace and control characters are skipped and character references are
+/// decoded, like browsers do.
+fn url_scheme(url: &str) -> Option<String> {
+    let mut scheme = String::new();
+    let mut rest = url;
+    while let Some(c) = rest.chars().next() {
+        let (c, len) = decode_char_reference(rest).unwrap_or((c, c.len_utf8()));
+        rest = &rest[len..];
+        match c {
+            ':' if !scheme.is_empty() => return Some(scheme),
+            c if c.is_ascii_whitespace() || c.is_control() => (),
+            c if c.is_ascii_alphanumeric() || "+-.".contains(c) => {
+                scheme.push(c.to_ascii_lowercase())
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Keep only the nodes at the paths, their descendants and their ancestors
+fn prune_nodes(nodes: &mut Vec<Node>, paths: &[Vec<usize>], path: &mut Vec<usize>) {
+    let mut index = 0;
+    nodes.retain_mut(|node| {
+        path.push(index);
+        index += 1;
+
+        let keep = if paths.iter().any(|p| p == path) {
+            true
+        } else if paths.iter().any(|p| p.starts_with(path)) {
+            if let Node::Element(element) = node {
+                prune_nodes(&mut element.children, paths, path);
+            }
+            true
+        } else {
+            false
+        };
+
+        path.pop();
+        keep
+    });
+}
+
+/// How the nodes are compared by `nodes_equal`
+#[derive(Clone, Copy)]
+enum Equality {
+    /// Collapse the whitespace of text and comments, see `Dom::equals_ignoring_whitespace`
+    IgnoringWhitespace,
+    /// Trim text and comments and compare the classes as sets, see `Dom::semantically_eq`
+    Semantic,
+}
+
+impl Equality {
+    fn text_eq(self, a: &str, b: &str) -> bool {
+        match self {
+            Equality::IgnoringWhitespace => a.split_whitespace().eq(b.split_whitespace()),
+            Equality::Semantic => a.trim() == b.trim(),
+        }
+    }
+
+    fn classes_eq(self, a: &[Cow<str>], b: &[Cow<str>]) -> bool {
+        match self {
+            Equality::IgnoringWhitespace => a == b,
+            Equality::Semantic => {
+                let a: BTreeSet<&str> = a.iter().map(|c| c.trim()).collect();
+                let b: BTreeSet<&str> = b.iter().map(|c| c.trim()).collect();
+                a == b
+            }
+        }
+    }
+}
+
+fn nodes_equal(a: &[Node], b: &[Node], equality: Equality) -> bool {
+    // whitespace only text nodes don't count as children
+    let significant = |n: &&Node| !matches!(n, Node::Text(t) if t.trim().is_empty());
+    let mut a = a.iter().filter(significant);
+    let mut b = b.iter().filter(significant);
+
+    loop {
+        let equal = match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(Node::Text(a)), Some(Node::Text(b))) => equality.text_eq(a, b),
+            (Some(Node::Comment(a)), Some(Node::Comment(b))) => equality.text_eq(a, b),
+            (Some(Node::CData(a)), Some(Node::CData(b))) => a == b,
+            (Some(Node::ProcessingInstruction(a)), Some(Node::ProcessingInstruction(b))) => a == b,
+            (Some(Node::Element(a)), Some(Node::Element(b))) => {
+                a.name == b.name
+                    && a.variant == b.variant
+                    && a.attributes == b.attributes
+                    && equality.classes_eq(&a.classes, &b.classes)
+                    && nodes_equal(&a.children, &b.children, equality)
+            }
+            (
+                Some(Node::ConditionalComment {
+                    condition: a_condition,
+                    children: a,
+                }),
+                Some(Node::ConditionalComment {
+                    condition: b_condition,
+                    children: b,
+                }),
+            ) => a_condition == b_condition && nodes_equal(a, b, equality),
+            _ => false,
+        };
+        if !equal {
+            return false;
+        }
+    }
+}
+
+/// Clone the nodes without whitespace only text and with collapsed whitespace in text and
+/// comments, like it's compared by `Dom::equals_ignoring_whitespace`
+fn canonical_nodes<'s>(nodes: &[Node<'s>]) -> Vec<Node<'s>> {
+    let collapse = |t: &str| Cow::Owned(t.split_whitespace().collect::<Vec<_>>().join(" "));
+    nodes
+        .iter()
+        .filter(|n| !matches!(n, Node::Text(t) if t.trim().is_empty()))
+        .map(|node| match node {
+            Node::Text(t) => Node::Text(collapse(t)),
+            Node::Comment(c) => Node::Comment(collapse(c)),
+            Node::CData(c) => Node::CData(c.clone()),
+            Node::ProcessingInstruction(p) => Node::ProcessingInstruction(p.clone()),
+            Node::Element(e) => Node::Element(Element {
+                children: canonical_nodes(&e.children),
+                ..e.clone()
+            }),
+            Node::ConditionalComment {
+                condition,
+                children,
+            } => Node::ConditionalComment {
+                condition: condition.clone(),
+                children: canonical_nodes(children),
+            },
+        })
+        .collect()
+}
+
+/// Write the json value with the keys of every object sorted. The order is not left to the map
+/// of `serde_json`, which keeps the insertion order with its `preserve_order` feature.
+fn write_canonical_json(value: &serde_json::Value, json: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            json.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                json.push_str(&serde_json::Value::String(key.clone()).to_string());
+                json.push(':');
+                write_canonical_json(value, json);
+            }
+            json.push('}');
+        }
+        serde_json::Value::Array(values) => {
+            json.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_canonical_json(value, json);
+            }
+            json.push(']');
+        }
+        value => json.push_str(&value.to_string()),
+    }
+}
+
 impl<'s> Display for Dom<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.fmt_opt(f, &FormattingOptions::pretty())
     }
 }
+
+/// Iterates over all nodes of the dom in document order, see `NodeIntoIterator`
+impl<'a> IntoIterator for &'a Dom<'a> {
+    type Item = &'a Node<'a>;
+    type IntoIter = NodeIntoIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        NodeIntoIterator::new(&self.children)
+    }
+}
+
+/// Parse an owned dom, which doesn't borrow from the input, see `Dom::into_owned`
+impl FromStr for Dom<'static> {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Ok(Dom::parse(input)?.into_owned())
+    }
+}
+
+impl<'s> TryFrom<&'s str> for Dom<'s> {
+    type Error = Error;
+
+    fn try_from(input: &'s str) -> Result<Self> {
+        Dom::parse(input)
+    }
+}
+
+/// Parse a bare list of attributes, like `class="a b" data-x="1" hidden`, which isn't surrounded
+/// by a tag. Template systems often emit such fragments.
+///
+/// Like the attributes of a parsed element, the `class` attribute is split into the classes,
+/// which are returned first, followed by all other attributes. When a key appears multiple
+/// times, the first declaration wins, as it does in the html spec. A fragment which isn't a
+/// valid attribute list returns an error.
+pub fn parse_attributes(input: &str) -> Result<(Vec<Cow<'_, str>>, Attributes<'_>)> {
+    let pairs = match Grammar::parse(Rule::attr_list, input) {
+        Ok(pairs) => pairs,
+        Err(error) => return Err(formatting::error_msg(error)),
+    };
+
+    let mut element = Element::default();
+    let mut has_class_attribute = false;
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::attr => match Node::build_attribute(pair.into_inner())? {
+                (key, value) if key.eq_ignore_ascii_case("class") => {
+                    if let (false, Some(classes)) = (has_class_attribute, value) {
+                        node::push_classes(&mut element, Cow::Borrowed(classes));
+                    }
+                    has_class_attribute = true;
+                }
+                (key, value) => {
+                    element
+                        .attributes
+                        .entry(Cow::Borrowed(key))
+                        .or_insert(value.map(Cow::Borrowed));
+                }
+            },
+            Rule::EOI => (),
+            _ => {
+                return Err(Error::Parsing(format!(
+                    "Failed to parse attribute list at rule: {:?}",
+                    pair.as_rule()
+                )))
+            }
+        }
+    }
+    Ok((element.classes, element.attributes))
+}
@@ -5,10 +5,19 @@ use crate::{
     ElementVariant, Error,
 };
 
-use super::{element::Element, formatting, options::FormattingOptions, span::SourceSpan, Result};
+use super::{
+    element::{Attributes, Element},
+    entities::{decode_entities, encode_text},
+    formatting, intern,
+    options::{FormattingOptions, ParseOptions},
+    span::SourceSpan,
+    visitor::{walk_node, Visitor},
+    warning::Warning,
+    Result,
+};
 use pest::{
     iterators::{Pair, Pairs},
-    Parser,
+    Parser, Position, Token,
 };
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +29,22 @@ pub enum Node<'s> {
     Text(Cow<'s, str>),
     #[serde(borrow)]
     Comment(Cow<'s, str>),
+    /// A CDATA section like `<![CDATA[a < b]]>`, the content is kept verbatim without decoding
+    /// or encoding of entities
+    #[serde(borrow)]
+    CData(Cow<'s, str>),
+    /// A processing instruction like the xml declaration `<?xml version="1.0"?>` at the start of
+    /// the document, it's kept as it's written in the source including `<?` and `?>`
+    #[serde(borrow)]
+    ProcessingInstruction(Cow<'s, str>),
+    /// A conditional comment like `<!--[if IE]><p>IE</p><![endif]-->` with the parsed content,
+    /// see `ParseOptions::conditional_comments`
+    ConditionalComment {
+        #[serde(borrow)]
+        condition: Cow<'s, str>,
+        #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+        children: Vec<Node<'s>>,
+    },
 }
 
 impl<'s> Node<'s> {
@@ -32,13 +57,28 @@ impl<'s> Node<'s> {
     }
 
     /// Get the elemnt when it's a element node
-    pub fn element(&self) -> Option<&Element> {
+    pub fn element(&self) -> Option<&Element<'_>> {
         match self {
             Node::Element(e) => Some(e),
             _ => None,
         }
     }
 
+    /// Walk over the node and all descendants in document order with the visitor, the node
+    /// itself has the depth `0`, see `Visitor`
+    pub fn walk<V>(&self, visitor: &mut V)
+    where
+        V: Visitor + ?Sized,
+    {
+        walk_node(self, visitor, 0);
+    }
+
+    /// Iterate over the element itself and all descendant elements in document order, text and
+    /// comments are skipped
+    pub fn descendant_elements(&self) -> impl Iterator<Item = &Element<'_>> {
+        std::iter::once(self).chain(self).filter_map(Node::element)
+    }
+
     /// Get the comment when it's a comment node
     pub fn comment(&self) -> Option<&str> {
         match self {
@@ -47,6 +87,69 @@ impl<'s> Node<'s> {
         }
     }
 
+    /// Get the content when it's a CDATA section
+    pub fn cdata(&self) -> Option<&str> {
+        match self {
+            Node::CData(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Get the child nodes of elements and conditional comments
+    pub(super) fn child_nodes(&self) -> &[Node<'s>] {
+        match self {
+            Node::Element(e) => &e.children,
+            Node::ConditionalComment { children, .. } => children,
+            _ => &[],
+        }
+    }
+
+    /// The mutable child nodes of an element or conditional comment, empty for all other nodes
+    pub(super) fn child_nodes_mut(&mut self) -> &mut [Node<'s>] {
+        match self {
+            Node::Element(e) => &mut e.children,
+            Node::ConditionalComment { children, .. } => children,
+            _ => &mut [],
+        }
+    }
+
+    /// The text of the node and all descendant text nodes, see `Element::text_content`
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        self.push_text_content(false, &mut text, &mut false);
+        text
+    }
+
+    /// The text of the node and all descendant text nodes, see `Element::text_content_spaced`
+    pub fn text_content_spaced(&self) -> String {
+        let mut text = String::new();
+        self.push_text_content(true, &mut text, &mut false);
+        text
+    }
+
+    pub(super) fn push_text_content(&self, spaced: bool, text: &mut String, space: &mut bool) {
+        match self {
+            Node::Text(t) | Node::CData(t) => {
+                let separated =
+                    text.ends_with(char::is_whitespace) || t.starts_with(char::is_whitespace);
+                if *space && !text.is_empty() && !separated {
+                    text.push(' ');
+                }
+                text.push_str(t);
+                *space = false;
+            }
+            Node::Element(e) => {
+                // block elements are separated from the text before and after
+                let is_block = spaced && e.matches_any_tag(Element::BLOCK_ELEMENTS);
+                *space |= is_block;
+                e.push_text_content(spaced, text, space);
+                *space |= is_block;
+            }
+            Node::Comment(_) | Node::ProcessingInstruction(_) | Node::ConditionalComment { .. } => {
+            }
+        }
+    }
+
     /// Create a new text node
     pub fn new_text(text: &'s str) -> Self {
         Self::Text(Cow::Borrowed(text))
@@ -57,13 +160,78 @@ impl<'s> Node<'s> {
         Self::Comment(Cow::Borrowed(comment))
     }
 
+    /// Create a new element node, the same as `Node::from(element)`
+    pub fn new_element(element: Element<'s>) -> Self {
+        Self::Element(element)
+    }
+
+    /// Convert the node into an owned node, which doesn't borrow from the source. Unlike
+    /// `Node::deep_clone_owned` the already owned strings aren't copied.
+    pub fn into_owned(self) -> Node<'static> {
+        match self {
+            Node::Element(e) => Node::Element(e.into_owned()),
+            Node::Text(t) => Node::Text(Cow::Owned(t.into_owned())),
+            Node::Comment(c) => Node::Comment(Cow::Owned(c.into_owned())),
+            Node::CData(c) => Node::CData(Cow::Owned(c.into_owned())),
+            Node::ProcessingInstruction(p) => {
+                Node::ProcessingInstruction(Cow::Owned(p.into_owned()))
+            }
+            Node::ConditionalComment {
+                condition,
+                children,
+            } => Node::ConditionalComment {
+                condition: Cow::Owned(condition.into_owned()),
+                children: children.into_iter().map(Node::into_owned).collect(),
+            },
+        }
+    }
+
+    /// Clone the node into an owned node, which doesn't borrow from the source
+    pub fn deep_clone_owned(&self) -> Node<'static> {
+        match self {
+            Node::Element(e) => Node::Element(e.deep_clone_owned()),
+            Node::Text(t) => Node::Text(Cow::Owned(t.to_string())),
+            Node::Comment(c) => Node::Comment(Cow::Owned(c.to_string())),
+            Node::CData(c) => Node::CData(Cow::Owned(c.to_string())),
+            Node::ProcessingInstruction(p) => {
+                Node::ProcessingInstruction(Cow::Owned(p.to_string()))
+            }
+            Node::ConditionalComment {
+                condition,
+                children,
+            } => Node::ConditionalComment {
+                condition: Cow::Owned(condition.to_string()),
+                children: children.iter().map(Node::deep_clone_owned).collect(),
+            },
+        }
+    }
+
     /// Parse a dom from a html string
     pub fn parse(input: &'s str) -> Result<Vec<Self>> {
+        Self::parse_with(input, &ParseOptions::default())
+    }
+
+    /// Parse a dom from a html string with the given parse options
+    pub fn parse_with(input: &'s str, options: &ParseOptions) -> Result<Vec<Self>> {
         let pairs = match Grammar::parse(Rule::html, input) {
             Ok(pairs) => pairs,
             Err(error) => return Err(formatting::error_msg(error)),
         };
-        Self::build_nodes(pairs)
+        check_depth(&pairs, options.max_depth)?;
+        let mut warnings = Vec::new();
+        let nodes = Self::build_nodes(pairs, options, &mut warnings, 0)?;
+        check_strict(&warnings, options)?;
+        check_single_root(&nodes, options)?;
+        Ok(nodes)
+    }
+
+    /// Parse the nodes from a html string into owned nodes, which aren't bound to the lifetime
+    /// of the input, see `Node::into_owned`
+    pub fn parse_owned(input: &str) -> Result<Vec<Node<'static>>> {
+        Ok(Node::parse(input)?
+            .into_iter()
+            .map(Node::into_owned)
+            .collect())
     }
 
     /// Create the node from a json string
@@ -85,25 +253,87 @@ impl<'s> Node<'s> {
     where
         W: std::fmt::Write,
     {
+        // without new lines the node is written minified in a single line
+        if !o.new_lines {
+            return self.fmt_inline(f, o);
+        }
+
         match self {
             Node::Element(elem) => {
                 elem.fmt_opt(f, o, depth)?;
             }
+            Node::Text(text) if o.wrap_text => {
+                o.fmt_depth(f, depth)?;
+                fmt_wrapped_text(f, o, text, depth)?;
+            }
             Node::Text(text) => {
                 o.fmt_depth(f, depth)?;
-                write!(f, "{}", text.trim())?;
+                write!(f, "{}", encode_text(text.trim()))?;
             }
             Node::Comment(comment) => {
                 o.fmt_depth(f, depth)?;
-                write!(f, "<!-- {comment} -->")?;
+                fmt_comment(f, o, comment)?;
+            }
+            Node::CData(data) => {
+                o.fmt_depth(f, depth)?;
+                write!(f, "<![CDATA[{data}]]>")?;
+            }
+            Node::ProcessingInstruction(instruction) => {
+                o.fmt_depth(f, depth)?;
+                write!(f, "{instruction}")?;
+            }
+            Node::ConditionalComment {
+                condition,
+                children,
+            } => {
+                o.fmt_depth(f, depth)?;
+                fmt_condition_start(f, condition)?;
+                for child in children.iter() {
+                    o.fmt_new_line(f)?;
+                    child.fmt_opt(f, o, depth + o.tab_size as usize)?;
+                }
+                o.fmt_new_line(f)?;
+                o.fmt_depth(f, depth)?;
+                write!(f, "<![endif]-->")?;
             }
         }
 
         Ok(())
     }
 
-    fn build_nodes(pairs: Pairs<'s, Rule>) -> Result<Vec<Self>> {
+    /// Write the node in a single line without any formatting whitespace. Whitespace in text is
+    /// collapsed, but not removed, because it's significant for the rendering of inline content.
+    pub(super) fn fmt_inline<W>(&self, f: &mut W, o: &FormattingOptions) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        match self {
+            Node::Element(elem) => elem.fmt_inline(f, o),
+            Node::Text(text) => fmt_collapsed_text(f, text),
+            Node::Comment(comment) => fmt_comment(f, o, comment),
+            Node::CData(data) => write!(f, "<![CDATA[{data}]]>"),
+            Node::ProcessingInstruction(instruction) => write!(f, "{instruction}"),
+            Node::ConditionalComment {
+                condition,
+                children,
+            } => {
+                fmt_condition_start(f, condition)?;
+                for child in children.iter() {
+                    child.fmt_inline(f, o)?;
+                }
+                write!(f, "<![endif]-->")
+            }
+        }
+    }
+
+    fn build_nodes(
+        pairs: Pairs<'s, Rule>,
+        options: &ParseOptions,
+        warnings: &mut Vec<Warning>,
+        depth: usize,
+    ) -> Result<Vec<Self>> {
         let mut nodes = Vec::new();
+        let mut unclosed = UnclosedElements::default();
 
         for pair in pairs {
             match pair.as_rule() {
@@ -111,45 +341,147 @@ impl<'s> Node<'s> {
                 Rule::doctype => (),
 
                 // If we see an element, build the sub-tree and add it as a child.
-                // Warnings are ignored
-                Rule::node_element => match Self::build_node_element(pair, &mut Vec::new()) {
-                    Ok(el) => {
-                        if let Some(node) = el {
-                            nodes.push(node);
+                Rule::node_element => {
+                    let (line, column) = pair.line_col();
+                    let start = UnclosedElements::start(&pair);
+                    match Self::build_node_element(pair, options, warnings, false, false, depth) {
+                        Ok(el) => {
+                            unclosed.push(start, &el, nodes.len());
+                            if let Some(node) = el {
+                                push_node(&mut nodes, node);
+                            }
+                        }
+                        Err(error) => {
+                            warnings.push(Warning::parse_error(error, line, column));
                         }
                     }
-                    Err(_) => {}
-                },
+                }
 
                 // Similar to an element, we add it as a child
                 Rule::node_text => {
                     let text = pair.as_str();
                     if !text.trim().is_empty() {
-                        nodes.push(Node::Text(Cow::Borrowed(text)));
+                        push_node(
+                            &mut nodes,
+                            Node::Text(build_node_text(text, options, false)),
+                        );
                     }
                 }
 
                 // Store comments as a child
                 Rule::node_comment => {
-                    nodes.push(Node::Comment(Cow::Borrowed(pair.into_inner().as_str())));
+                    nodes.extend(Self::build_node_comment(pair, options, warnings, depth));
                 }
 
-                // Ignore 'end of input', which then allows the catch-all unreachable!() arm to
-                // function properly.
-                Rule::EOI => (),
+                // The content of CDATA sections is kept as it is
+                Rule::node_cdata => nodes.push(build_node_cdata(pair)),
+
+                // Store the xml declaration as it's written
+                Rule::node_process_instruct => nodes.push(build_node_process_instruct(pair)),
+
+                // The 'end of input' ends the content of unclosed elements, it's matched to
+                // allow the catch-all error arm to function properly.
+                Rule::EOI => unclosed.end(&pair),
 
                 // This should be unreachable, due to the way the grammar is written
-                _ => unreachable!("[build nodes] unknown rule: {:?}", pair.as_rule()),
+                _ => {
+                    return Err(Error::Parsing(format!(
+                        "[build nodes] unknown rule: {:?}",
+                        pair.as_rule()
+                    )))
+                }
             };
         }
 
+        unclosed.nest(&mut nodes, options, false)?;
+
         // The result are validated nodes
         Ok(nodes)
     }
 
+    /// Build the comment node, returns `None` when comments aren't kept. The elements of a parsed
+    /// conditional comment have the given depth.
+    pub(super) fn build_node_comment(
+        pair: Pair<'s, Rule>,
+        options: &ParseOptions,
+        warnings: &mut Vec<Warning>,
+        depth: usize,
+    ) -> Option<Node<'s>> {
+        if !options.keep_comments {
+            return None;
+        }
+        Some(Self::build_comment(pair, options, warnings, depth))
+    }
+
+    fn build_comment(
+        pair: Pair<'s, Rule>,
+        options: &ParseOptions,
+        warnings: &mut Vec<Warning>,
+        depth: usize,
+    ) -> Node<'s> {
+        let source = pair.as_str().trim();
+        let inner = pair.into_inner();
+        let is_conditional = matches!(
+            inner.peek().map(|p| p.as_rule()),
+            Some(Rule::comment_if_condition | Rule::comment_if_body)
+        );
+
+        // conditional comments are kept verbatim between `<!--` and `-->`, so they are written
+        // back exactly like in the source
+        let comment = match is_conditional {
+            true => Node::Comment(Cow::Borrowed(&source[4..source.len() - 3])),
+            false => Node::Comment(Cow::Borrowed(inner.as_str())),
+        };
+        if !options.conditional_comments {
+            return comment;
+        }
+
+        // Only conditional comments with a condition have a content to parse
+        let mut inner = inner;
+        let (condition, body) = match (inner.next(), inner.next()) {
+            (Some(condition), Some(body)) if condition.as_rule() == Rule::comment_if_condition => {
+                (condition, body)
+            }
+            _ => return comment,
+        };
+
+        let (line, column) = body.line_col();
+        let mut children = match Grammar::parse(Rule::html, body.as_str()) {
+            Ok(pairs) => match check_depth(&pairs, options.max_depth)
+                .and_then(|_| Self::build_nodes(pairs, options, warnings, depth))
+            {
+                Ok(children) => children,
+                Err(error) => {
+                    warnings.push(Warning::parse_error(error, line, column));
+                    return comment;
+                }
+            },
+            Err(error) => {
+                let error = formatting::error_msg(error);
+                warnings.push(Warning::parse_error(error, line, column));
+                return comment;
+            }
+        };
+
+        // The content was parsed on its own, move the spans to the position in the source
+        shift_source_spans(&mut children, line, column);
+
+        Node::ConditionalComment {
+            condition: Cow::Borrowed(condition.as_str().trim()),
+            children,
+        }
+    }
+
+    /// Build the element at the nesting depth, `preformatted` is set within `pre` and `textarea`
+    /// elements to keep the whitespace of the text and `foreign` within `svg` and `math`
+    /// elements, where self-closing elements are void, see `Element::is_foreign`
     pub(super) fn build_node_element(
         pair: Pair<'s, Rule>,
-        warnings: &mut Vec<String>,
+        options: &ParseOptions,
+        warnings: &mut Vec<Warning>,
+        preformatted: bool,
+        foreign: bool,
+        depth: usize,
     ) -> Result<Option<Node<'s>>> {
         let source_span = {
             let pair_span = pair.as_span();
@@ -165,71 +497,171 @@ impl<'s> Node<'s> {
             )
         };
 
+        // count the attributes and children first, so the collections are allocated only once.
+        // The classes aren't stored as attributes.
+        let (mut attributes_len, mut children_len) = (0, 0);
+        for pair in pair.clone().into_inner() {
+            match pair.as_rule() {
+                Rule::attr => {
+                    let key = pair.into_inner().next().map(|key| key.as_str().trim());
+                    if !key.is_some_and(|key| key.eq_ignore_ascii_case("class")) {
+                        attributes_len += 1;
+                    }
+                }
+                Rule::node_element
+                | Rule::el_raw_text
+                | Rule::node_text
+                | Rule::el_raw_text_content
+                | Rule::node_comment
+                | Rule::node_cdata => children_len += 1,
+                _ => (),
+            }
+        }
+
+        let source = pair.as_str();
+        let warnings_len = warnings.len();
         let mut element = Element {
             source_span,
+            depth,
+            attributes: Attributes::with_capacity(attributes_len),
+            children: Vec::with_capacity(children_len),
             ..Element::default()
         };
+        let mut has_class_attribute = false;
+        // the whitespace of the content is kept within preformatted elements
+        let mut keep_whitespace = preformatted;
+        let mut unclosed = UnclosedElements::default();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
                 Rule::node_element | Rule::el_raw_text => {
-                    match Self::build_node_element(pair, warnings) {
+                    let (line, column) = pair.line_col();
+                    let start = UnclosedElements::start(&pair);
+                    match Self::build_node_element(
+                        pair,
+                        options,
+                        warnings,
+                        keep_whitespace,
+                        foreign || element.is_foreign(),
+                        depth + 1,
+                    ) {
+                        Ok(Some(text @ Node::Text(_))) => push_node(&mut element.children, text),
                         Ok(el) => {
-                            if let Some(child_element) = el {
-                                element.children.push(child_element)
-                            }
+                            unclosed.push(start, &el, element.children.len());
+                            element.children.extend(el);
                         }
                         Err(error) => {
-                            warnings.push(format!("{}", error));
+                            warnings.push(Warning::parse_error(error, line, column));
                         }
                     }
                 }
                 Rule::node_text | Rule::el_raw_text_content => {
+                    // whitespace after an element is kept, until we know if it's followed by
+                    // another element. The formatting decides if it's significant, see
+                    // `FormattingOptions::inline_elements`
                     let text = pair.as_str();
-                    if !text.trim().is_empty() {
-                        element.children.push(Node::Text(Cow::Borrowed(text)));
+                    if !text.trim().is_empty()
+                        || keep_whitespace
+                        || matches!(element.children.last(), Some(Node::Element(_)))
+                    {
+                        // only escapable raw text is decoded, but never script or style
+                        let text = match pair.as_rule() {
+                            Rule::node_text => build_node_text(text, options, keep_whitespace),
+                            _ if element.matches_any_tag(&["title", "textarea"]) => {
+                                build_text(text, options)
+                            }
+                            _ => Cow::Borrowed(text),
+                        };
+                        push_node(&mut element.children, Node::Text(text));
                     }
                 }
                 Rule::node_comment => {
-                    element
-                        .children
-                        .push(Node::Comment(Cow::Borrowed(pair.into_inner().as_str())));
+                    if !keep_whitespace {
+                        pop_whitespace_text(&mut element.children);
+                    }
+                    element.children.extend(Self::build_node_comment(
+                        pair,
+                        options,
+                        warnings,
+                        depth + 1,
+                    ));
                 }
+                Rule::node_cdata => element.children.push(build_node_cdata(pair)),
                 // TODO: To enable some kind of validation we should probably align this with
                 // https://html.spec.whatwg.org/multipage/syntax.html#elements-2
                 // Also see element variants
                 Rule::el_name | Rule::el_void_name | Rule::el_raw_text_name => {
-                    element.name = Cow::Borrowed(pair.as_str());
+                    element.name = build_name(pair.as_str(), options);
+                    keep_whitespace |= element.matches_any_tag(&["pre", "textarea"]);
                 }
-                Rule::attr => match Self::build_attribute(pair.into_inner()) {
-                    Ok((attr_key, attr_value)) => {
-                        match attr_key {
-                            "class" => {
-                                if let Some(classes) = attr_value {
-                                    let classes = classes.split_whitespace().collect::<Vec<_>>();
-                                    for class in classes {
-                                        element.classes.push(Cow::Borrowed(class));
-                                    }
-                                }
-                            }
-                            _ => {
-                                element.attributes.insert(
-                                    Cow::Borrowed(attr_key),
-                                    attr_value.map(|s| Cow::Borrowed(s)),
-                                );
+                Rule::attr => {
+                    let (line, column) = pair.line_col();
+                    let attribute = Self::build_attribute(pair.into_inner())
+                        .map(|(key, value)| (build_name(key, options), value));
+                    let is_class = |key: &str| key.eq_ignore_ascii_case("class");
+                    match attribute {
+                        // the first declaration of an attribute wins, like in the html spec
+                        Ok((attr_key, _))
+                            if (is_class(&attr_key) && has_class_attribute)
+                                || element.has_attribute(&attr_key) =>
+                        {
+                            warnings.push(Warning::DuplicateAttribute {
+                                name: attr_key.to_string(),
+                                element: element.name.to_string(),
+                                line,
+                                column,
+                            });
+                        }
+                        Ok((attr_key, attr_value)) if is_class(&attr_key) => {
+                            has_class_attribute = true;
+                            if let Some(classes) = attr_value {
+                                push_classes(&mut element, build_text(classes, options));
                             }
-                        };
-                    }
-                    Err(error) => {
-                        warnings.push(format!("{}", error));
+                        }
+                        Ok((attr_key, attr_value)) => {
+                            element
+                                .attributes
+                                .insert(attr_key, attr_value.map(|v| build_text(v, options)));
+                        }
+                        Err(error) => {
+                            warnings.push(Warning::AttributeParseError {
+                                message: error.to_string(),
+                                line,
+                                column,
+                            });
+                        }
                     }
-                },
-                Rule::el_normal_end | Rule::el_raw_text_end => {
+                }
+                Rule::el_normal_end | Rule::el_implied_end => {
                     element.variant = ElementVariant::Normal;
+                    unclosed.end(&pair);
+                    break;
+                }
+                Rule::el_raw_text_end => {
+                    element.variant = ElementVariant::RawText;
                     break;
                 }
-                Rule::el_dangling => (),
-                Rule::EOI => (),
+                // a start tag without end tag is kept as text, unless it's a void element or a
+                // html element like `<div>`, which takes the following siblings as children, see
+                // `UnclosedElements`
+                Rule::el_unclosed_end => {
+                    let is_html_tag = intern::is_html_element(&element.name)
+                        && !source[1..].starts_with(char::is_whitespace);
+                    if !options.is_void(&element.name) && !is_html_tag {
+                        warnings.truncate(warnings_len);
+                        let text = build_node_text(source, options, preformatted);
+                        return Ok(Some(Node::Text(text)));
+                    }
+                }
+                Rule::el_dangling => {
+                    let (line, column) = pair.line_col();
+                    warnings.push(Warning::DanglingElement {
+                        name: pair.into_inner().as_str().to_string(),
+                        line,
+                        column,
+                    });
+                }
+                Rule::EOI => unclosed.end(&pair),
                 _ => {
                     return Err(Error::Parsing(format!(
                         "Failed to create element at rule: {:?}",
@@ -238,14 +670,30 @@ impl<'s> Node<'s> {
                 }
             }
         }
-        if element.name != "" {
+        if !keep_whitespace {
+            pop_whitespace_text(&mut element.children);
+        }
+        unclosed.nest(&mut element.children, options, keep_whitespace)?;
+
+        // html elements are only void when they are html void elements, `<div/>` is a normal
+        // element. The variant of other elements and of foreign content like `<svg><a/></svg>`
+        // is defined by the grammar.
+        if element.variant == ElementVariant::Void
+            && !foreign
+            && !options.is_void(&element.name)
+            && intern::is_html_element(&element.name)
+        {
+            element.variant = ElementVariant::Normal;
+        }
+
+        if !element.name.is_empty() {
             Ok(Some(Node::Element(element)))
         } else {
             Ok(None)
         }
     }
 
-    fn build_attribute(pairs: Pairs<'s, Rule>) -> Result<(&'s str, Option<&'s str>)> {
+    pub(super) fn build_attribute(pairs: Pairs<'s, Rule>) -> Result<(&'s str, Option<&'s str>)> {
         let mut attribute = ("", None);
         for pair in pairs {
             match pair.as_rule() {
@@ -256,11 +704,14 @@ impl<'s> Node<'s> {
                     attribute.1 = Some(pair.as_str().trim());
                 }
                 Rule::attr_quoted => {
-                    let inner_pair = pair
-                        .into_inner()
-                        .into_iter()
-                        .next()
-                        .expect("attribute value");
+                    let inner_pair = match pair.into_inner().next() {
+                        Some(inner_pair) => inner_pair,
+                        None => {
+                            return Err(Error::Parsing(
+                                "Failed to parse attr value: missing value".to_string(),
+                            ))
+                        }
+                    };
 
                     match inner_pair.as_rule() {
                         Rule::attr_value => attribute.1 = Some(inner_pair.as_str()),
@@ -284,84 +735,554 @@ impl<'s> Node<'s> {
     }
 }
 
+/// Write the start of a conditional comment, like `<!--[if IE]>`
+fn fmt_condition_start<W>(f: &mut W, condition: &str) -> std::fmt::Result
+where
+    W: std::fmt::Write,
+{
+    match condition.is_empty() {
+        true => write!(f, "<!--[if]>"),
+        false => write!(f, "<!--[if {condition}]>"),
+    }
+}
+
+/// Move the source spans of nodes, which were parsed from a part of the source, so they point to
+/// the line and column of the part within the whole source.
+pub(super) fn shift_source_spans(nodes: &mut [Node], line: usize, column: usize) {
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Element(e) => {
+                let span = &mut e.source_span;
+                if span.start_line == 1 {
+                    span.start_column += column - 1;
+                }
+                if span.end_line == 1 {
+                    span.end_column += column - 1;
+                }
+                span.start_line += line - 1;
+                span.end_line += line - 1;
+                shift_source_spans(&mut e.children, line, column);
+            }
+            Node::ConditionalComment { children, .. } => shift_source_spans(children, line, column),
+            _ => (),
+        }
+    }
+}
+
+/// Add the offset to the depth of the elements, which were parsed on their own at the top level
+pub(super) fn shift_depths(nodes: &mut [Node], offset: usize) {
+    for node in nodes.iter_mut() {
+        if let Node::Element(e) = node {
+            e.depth += offset;
+        }
+        shift_depths(node.child_nodes_mut(), offset);
+    }
+}
+
+/// Check the nesting depth of the elements without recursion, deeper input is rejected before
+/// the tree is built recursively
+pub(super) fn check_depth(pairs: &Pairs<Rule>, max_depth: usize) -> Result<()> {
+    let mut depth = 0;
+    for token in pairs.clone().tokens() {
+        match token {
+            Token::Start {
+                rule: Rule::node_element,
+                pos,
+            } => {
+                depth += 1;
+                if depth > max_depth {
+                    let (line, column) = pos.line_col();
+                    return Err(Error::Parsing(format!(
+                        "The maximum depth of {} nested elements is exceeded at line {}, column {}",
+                        max_depth, line, column
+                    )));
+                }
+            }
+            Token::End {
+                rule: Rule::node_element,
+                ..
+            } => depth -= 1,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Return an error for the first dangling end tag in strict mode, see `ParseOptions::strict`
+pub(super) fn check_strict(warnings: &[Warning], options: &ParseOptions) -> Result<()> {
+    if !options.strict {
+        return Ok(());
+    }
+    for warning in warnings {
+        if let Warning::DanglingElement { name, line, column } = warning {
+            return Err(Error::Parsing(format!(
+                "Dangling end tag `</{}>` at line {}, column {}",
+                name, line, column
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Return an error for more than one element at the top level, see `ParseOptions::single_root`
+pub(super) fn check_single_root(nodes: &[Node], options: &ParseOptions) -> Result<()> {
+    if !options.single_root {
+        return Ok(());
+    }
+    let roots = nodes.iter().filter(|n| n.element().is_some()).count();
+    if roots > 1 {
+        return Err(Error::Parsing(format!(
+            "A single root element is expected, but {} root elements were found",
+            roots
+        )));
+    }
+    Ok(())
+}
+
+/// Create the name of an element or attribute, which is lowercase when enabled by the options
+fn build_name<'s>(name: &'s str, options: &ParseOptions) -> Cow<'s, str> {
+    if !options.lowercase_names || !name.contains(|c: char| c.is_uppercase()) {
+        return Cow::Borrowed(name);
+    }
+    match intern::static_name_lowercase(name) {
+        Some(name) => Cow::Borrowed(name),
+        None => Cow::Owned(name.to_lowercase()),
+    }
+}
+
+/// Create the text of a text node, which has the whitespace collapsed when enabled by the
+/// options, except for preformatted text
+pub(super) fn build_node_text<'s>(
+    text: &'s str,
+    options: &ParseOptions,
+    preformatted: bool,
+) -> Cow<'s, str> {
+    let text = build_text(text, options);
+    match options.collapse_whitespace && !preformatted {
+        true => collapse_whitespace(text),
+        false => text,
+    }
+}
+
+/// Replace every run of ascii whitespace with a single space, like css `white-space: normal`
+fn collapse_whitespace(text: Cow<str>) -> Cow<str> {
+    let is_collapsed =
+        !text.contains(|c: char| c.is_ascii_whitespace() && c != ' ') && !text.contains("  ");
+    if is_collapsed {
+        return text;
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut previous_whitespace = false;
+    for c in text.chars() {
+        match c.is_ascii_whitespace() {
+            true if previous_whitespace => (),
+            true => result.push(' '),
+            false => result.push(c),
+        }
+        previous_whitespace = c.is_ascii_whitespace();
+    }
+    Cow::Owned(result)
+}
+
+/// Create the text, which has the character references decoded when enabled by the options
+pub(super) fn build_text<'s>(text: &'s str, options: &ParseOptions) -> Cow<'s, str> {
+    match options.decode_entities {
+        true => decode_entities(text),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// Add the classes of a `class` attribute value to the element, repeated classes are only
+/// kept once, in the first seen order
+pub(super) fn push_classes<'s>(element: &mut Element<'s>, classes: Cow<'s, str>) {
+    match classes {
+        Cow::Borrowed(classes) => {
+            for class in split_classes(classes) {
+                if !element.has_class(class) {
+                    element.classes.push(Cow::Borrowed(class));
+                }
+            }
+        }
+        Cow::Owned(classes) => {
+            for class in split_classes(&classes) {
+                if !element.has_class(class) {
+                    element.classes.push(Cow::Owned(class.to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// Split the classes at whitespace, but keep template interpolations like `{{ active }}`,
+/// `{% if a %}` or `{# note #}` together with the surrounding characters as one class.
+/// The classes are trimmed and never empty, so `"  a   b "` is split into `["a", "b"]`.
+fn split_classes(classes: &str) -> Vec<&str> {
+    const INTERPOLATIONS: [(&str, &str); 3] = [("{{", "}}"), ("{%", "%}"), ("{#", "#}")];
+
+    let mut result = vec![];
+    let mut start = None;
+    let mut i = 0;
+    while let Some(c) = classes[i..].chars().next() {
+        let rest = &classes[i..];
+
+        // skip a complete interpolation, so its whitespace doesn't split the class
+        let interpolation = INTERPOLATIONS
+            .iter()
+            .find(|(open, _)| rest.starts_with(open))
+            .and_then(|(open, close)| {
+                rest[open.len()..]
+                    .find(close)
+                    .map(|e| e + open.len() + close.len())
+            });
+        if let Some(len) = interpolation {
+            start.get_or_insert(i);
+            i += len;
+            continue;
+        }
+
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                result.push(&classes[s..i]);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+        i += c.len_utf8();
+    }
+    if let Some(s) = start {
+        result.push(&classes[s..]);
+    }
+    result
+}
+
+/// Build a `Node::CData` with the verbatim content of the section
+pub(super) fn build_node_cdata(pair: Pair<'_, Rule>) -> Node<'_> {
+    let body = pair
+        .into_inner()
+        .next()
+        .map(|p| p.as_str())
+        .unwrap_or_default();
+    Node::CData(Cow::Borrowed(body))
+}
+
+/// Build a `Node::ProcessingInstruction` with the whole `<? ... ?>` text
+pub(super) fn build_node_process_instruct(pair: Pair<'_, Rule>) -> Node<'_> {
+    Node::ProcessingInstruction(Cow::Borrowed(pair.as_str().trim()))
+}
+
+/// Check if the content of a comment is a conditional comment, like `[if IE]><p>IE</p><![endif]`
+fn is_conditional_comment(comment: &str) -> bool {
+    let comment = comment.trim();
+    let starts_with_if = comment
+        .get(..3)
+        .is_some_and(|start| start.eq_ignore_ascii_case("[if"));
+    let ends_with_endif = comment
+        .len()
+        .checked_sub(7)
+        .and_then(|i| comment.get(i..))
+        .is_some_and(|end| end.eq_ignore_ascii_case("[endif]"));
+    starts_with_if && ends_with_endif
+}
+
+/// Write the trimmed comment with a space around the content, unless `comment_padding` is
+/// disabled or it's a conditional comment, which is written exactly as it's stored
+fn fmt_comment<W>(f: &mut W, o: &FormattingOptions, comment: &str) -> std::fmt::Result
+where
+    W: std::fmt::Write,
+{
+    match o.comment_padding && !is_conditional_comment(comment) {
+        true => write!(f, "<!-- {} -->", comment.trim()),
+        false => write!(f, "<!--{comment}-->"),
+    }
+}
+
+/// Write the text with collapsed whitespace and break the line before a word which exceeds the
+/// max length, the following lines are indented with the depth. A single word longer than the
+/// max length is never split.
+fn fmt_wrapped_text<W>(
+    f: &mut W,
+    o: &FormattingOptions,
+    text: &str,
+    depth: usize,
+) -> std::fmt::Result
+where
+    W: std::fmt::Write,
+{
+    fmt_wrapped_words(f, o, text.split_whitespace().map(encode_text), depth)
+}
+
+/// Write the words separated by a space or a line break, see `fmt_wrapped_text`
+pub(super) fn fmt_wrapped_words<W, I, S>(
+    f: &mut W,
+    o: &FormattingOptions,
+    words: I,
+    depth: usize,
+) -> std::fmt::Result
+where
+    W: std::fmt::Write,
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line_len = depth;
+    for (i, word) in words.enumerate() {
+        let word = word.as_ref();
+        let word_len = word.chars().count();
+        if i > 0 && line_len + 1 + word_len > o.max_len {
+            o.fmt_new_line(f)?;
+            o.fmt_depth(f, depth)?;
+            line_len = depth;
+        } else if i > 0 {
+            write!(f, " ")?;
+            line_len += 1;
+        }
+        write!(f, "{word}")?;
+        line_len += word_len;
+    }
+    Ok(())
+}
+
+/// Write the text with collapsed whitespace, a leading or trailing whitespace is kept as a space
+pub(super) fn fmt_collapsed_text<W>(f: &mut W, text: &str) -> std::fmt::Result
+where
+    W: std::fmt::Write,
+{
+    if text.starts_with(char::is_whitespace) {
+        write!(f, " ")?;
+    }
+    let mut words = text.split_whitespace().map(encode_text);
+    if let Some(word) = words.next() {
+        write!(f, "{word}")?;
+        for word in words {
+            write!(f, " {word}")?;
+        }
+        if text.ends_with(char::is_whitespace) {
+            write!(f, " ")?;
+        }
+    }
+    Ok(())
+}
+
+/// The unclosed html elements within a list of sibling nodes, like a `<div>` without `</div>`.
+/// They take the following siblings up to the end of the parent as their children.
+#[derive(Default)]
+pub(super) struct UnclosedElements<'s> {
+    starts: Vec<(usize, Position<'s>)>,
+    end: Option<Position<'s>>,
+}
+
+impl<'s> UnclosedElements<'s> {
+    /// Return the start of the element pair, when it's a start tag without end tag
+    pub(super) fn start(pair: &Pair<'s, Rule>) -> Option<Position<'s>> {
+        pair.clone()
+            .into_inner()
+            .any(|p| p.as_rule() == Rule::el_unclosed_end)
+            .then(|| pair.as_span().start_pos())
+    }
+
+    /// Remember the built node with the start of its pair, it's pushed at the index. Void
+    /// elements and start tags kept as text don't take any children.
+    pub(super) fn push(&mut self, start: Option<Position<'s>>, node: &Option<Node>, index: usize) {
+        if let (Some(start), Some(Node::Element(element))) = (start, node) {
+            if !element.is_void() {
+                self.starts.push((index, start));
+            }
+        }
+    }
+
+    /// Set the end of the content, which is the start of the end tag of the parent or the end
+    /// of the input
+    pub(super) fn end(&mut self, pair: &Pair<'s, Rule>) {
+        self.end = Some(pair.as_span().start_pos());
+    }
+
+    /// Move the following siblings into the unclosed elements, the last one first. The source
+    /// span of the elements is extended to the end of the content.
+    pub(super) fn nest(
+        self,
+        nodes: &mut Vec<Node<'s>>,
+        options: &ParseOptions,
+        keep_whitespace: bool,
+    ) -> Result<()> {
+        let Some(end) = self.end else {
+            return Ok(());
+        };
+        for (index, start) in self.starts.into_iter().rev() {
+            let mut children = nodes.split_off(index + 1);
+            let Some(Node::Element(element)) = nodes.get_mut(index) else {
+                nodes.extend(children);
+                continue;
+            };
+            if !keep_whitespace
+                && matches!(children.first(), Some(Node::Text(t)) if t.trim().is_empty())
+            {
+                children.remove(0);
+            }
+            if children.is_empty() {
+                continue;
+            }
+
+            shift_depths(&mut children, 1);
+            if max_element_depth(&children) >= options.max_depth {
+                return Err(Error::Parsing(format!(
+                    "The maximum depth of {} nested elements is exceeded by the unclosed element \
+                     at line {}, column {}",
+                    options.max_depth,
+                    element.source_span.start_line,
+                    element.source_span.start_column
+                )));
+            }
+
+            let input = start.span(&end).get_input();
+            let text = input[start.pos()..end.pos()].trim_end();
+            let end = Position::new(input, start.pos() + text.len()).unwrap_or(start);
+            let (end_line, end_column) = end.line_col();
+            element.source_span.text = Cow::Borrowed(text);
+            element.source_span.end_line = end_line;
+            element.source_span.end_column = end_column;
+            element.children = children;
+        }
+        Ok(())
+    }
+}
+
+/// Return the highest depth of the elements within the nodes
+fn max_element_depth(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Element(e) => e.depth.max(max_element_depth(&e.children)),
+            node => max_element_depth(node.child_nodes()),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Push the node to the children, a text is merged into a directly preceding text
+pub(super) fn push_node<'s>(children: &mut Vec<Node<'s>>, node: Node<'s>) {
+    match (children.last_mut(), node) {
+        (Some(Node::Text(last)), Node::Text(text)) => last.to_mut().push_str(&text),
+        (_, node) => children.push(node),
+    }
+}
+
+/// Merge the adjacent texts of the nodes and all descendants, empty texts are removed
+pub(super) fn normalize_nodes(nodes: &mut Vec<Node>) {
+    for mut node in std::mem::take(nodes) {
+        match &mut node {
+            Node::Text(text) if text.is_empty() => continue,
+            Node::Element(e) => normalize_nodes(&mut e.children),
+            Node::ConditionalComment { children, .. } => normalize_nodes(children),
+            _ => (),
+        }
+        push_node(nodes, node);
+    }
+}
+
+/// Keep only the nodes for which the function returns `true`, the descendants of the kept
+/// nodes are filtered as well
+pub(super) fn retain_nodes<F>(nodes: &mut Vec<Node>, f: &mut F)
+where
+    F: FnMut(&Node) -> bool,
+{
+    nodes.retain(|node| f(node));
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Element(e) => retain_nodes(&mut e.children, f),
+            Node::ConditionalComment { children, .. } => retain_nodes(children, f),
+            _ => (),
+        }
+    }
+}
+
+/// Remove a trailing whitespace only text, which isn't between two inline elements
+fn pop_whitespace_text(children: &mut Vec<Node>) {
+    if matches!(children.last(), Some(Node::Text(t)) if t.trim().is_empty()) {
+        children.pop();
+    }
+}
+
 impl<'s> Display for Node<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.fmt_opt(f, &FormattingOptions::pretty(), 0)
     }
 }
 
+impl<'s> From<Element<'s>> for Node<'s> {
+    fn from(element: Element<'s>) -> Self {
+        Self::new_element(element)
+    }
+}
+
+impl<'s> From<&'s str> for Node<'s> {
+    fn from(text: &'s str) -> Self {
+        Self::new_text(text)
+    }
+}
+
 impl<'a> IntoIterator for &'a Node<'a> {
     type Item = &'a Node<'a>;
     type IntoIter = NodeIntoIterator<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        NodeIntoIterator {
-            node: self,
-            index: vec![],
-        }
+        NodeIntoIterator::new(self.child_nodes())
     }
 }
 
+/// Iterates over the nodes in document order, every node is followed by its descendants
 pub struct NodeIntoIterator<'a> {
-    node: &'a Node<'a>,
-    // We add/remove to this vec each time we go up/down a node three
-    index: Vec<(usize, &'a Node<'a>)>,
+    // The siblings of each level down to the current node, with the index of the next sibling.
+    // We add/remove to this vec each time we go down/up the node tree
+    index: Vec<(usize, &'a [Node<'a>])>,
+}
+
+impl<'a> NodeIntoIterator<'a> {
+    /// Iterate over the given nodes and all of their descendants
+    pub(super) fn new(nodes: &'a [Node<'a>]) -> Self {
+        Self {
+            index: vec![(0, nodes)],
+        }
+    }
+}
+
+impl<'a> NodeIntoIterator<'a> {
+    /// The index path of the last returned node, starting at the iterated nodes.
+    /// Ex: `[1, 0]` is the first child of the second node, see `Dom::node_at`
+    pub fn path(&self) -> Vec<usize> {
+        let parents = self.index.len().saturating_sub(1);
+        self.index[..parents].iter().map(|(i, _)| i - 1).collect()
+    }
+
+    /// Yield the index path alongside each node, see `NodeIntoIterator::path`
+    pub fn with_paths(mut self) -> impl Iterator<Item = (Vec<usize>, &'a Node<'a>)> {
+        std::iter::from_fn(move || {
+            let node = self.next()?;
+            Some((self.path(), node))
+        })
+    }
 }
 
 impl<'a> Iterator for NodeIntoIterator<'a> {
     type Item = &'a Node<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Get first child
-        let child = match self.node {
-            Node::Element(ref e) => e.children.get(0),
-            _ => None,
-        };
-
-        let result = match child {
-            // If element has child, return child
-            Some(child) => {
-                self.index.push((0, self.node));
-                self.node = child;
-                Some(child)
-            }
-            // If element doesn't have a child, but is a child of another node
-            None if self.index.len() > 0 => {
-                let mut has_finished = false;
-                let mut next_node = None;
-
-                while !has_finished {
-                    // Try to get the next sibling of the parent node
-                    if let Some((sibling_index, parent)) = self.index.pop() {
-                        let next_sibling = sibling_index + 1;
-                        let sibling = if let Node::Element(ref e) = parent {
-                            e.children.get(next_sibling)
-                        } else {
-                            None
-                        };
-                        if sibling.is_some() {
-                            has_finished = true;
-                            self.index.push((next_sibling, parent));
-                            next_node = sibling;
-                        } else {
-                            continue;
-                        }
-                    // Break of there are no more parents
-                    } else {
-                        has_finished = true;
-                    }
+        while let Some((next_sibling, siblings)) = self.index.last_mut() {
+            let siblings: &'a [Node<'a>] = siblings;
+            match siblings.get(*next_sibling) {
+                // Return the node and continue with its children
+                Some(node) => {
+                    *next_sibling += 1;
+                    self.index.push((0, node.child_nodes()));
+                    return Some(node);
                 }
-
-                if let Some(next_node) = next_node {
-                    self.node = next_node;
+                // All siblings are visited, continue with the siblings of the parent
+                None => {
+                    self.index.pop();
                 }
-
-                next_node
             }
-            _ => None,
-        };
-
-        result
+        }
+        None
     }
 }
 
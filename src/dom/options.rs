@@ -1,17 +1,54 @@
+use super::element::Element;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
 #[derive(Debug, Clone)]
 pub struct FormattingOptions {
     /// Double quotation marks or single
     pub double_quot: bool,
 
-    /// Break tags in new line and split attributes when needed
+    /// Write attributes without value with their key as value like `disabled='disabled'`,
+    /// which is needed for xhtml. Otherwise they are written bare like `disabled`.
+    pub expand_boolean_attributes: bool,
+
+    /// Break tags in new line and split attributes when needed, otherwise the output is
+    /// minified, see `FormattingOptions::compact`
     pub new_lines: bool,
 
     /// Max length used for split attributes to new lines
     pub max_len: usize,
 
+    /// Wrap text longer than `max_len` at word boundaries into indented lines. The whitespace
+    /// of wrapped text is collapsed, the content of `pre` and `textarea` is never wrapped.
+    /// Inline content like `a <b>b</b> c` is wrapped at the whitespace of its text as well.
+    pub wrap_text: bool,
+
+    /// Write a space around the content of comments like `<!-- x -->`, otherwise the content
+    /// is written as it's stored like `<!--x-->`
+    pub comment_padding: bool,
+
     /// The amount of white spaces a tab is sized
     /// This will be needed to calculate the max length
     pub tab_size: u8,
+
+    /// Names of the inline elements. Content with inline elements is printed without
+    /// formatting whitespace, because it would change how the content is rendered. The
+    /// whitespace between elements is kept by the parser and only written next to inline
+    /// elements.
+    pub inline_elements: HashSet<String>,
+
+    /// The order in which the attributes are written
+    pub attribute_order: AttributeOrder,
+
+    /// The line ending written for every new line of the formatting
+    pub line_ending: LineEnding,
+
+    /// The whitespace written for every indentation level, the width used to calculate the
+    /// max length is still defined by `tab_size`
+    pub indent: IndentStyle,
+
+    /// How void elements are written, empty normal elements always keep their end tag
+    pub syntax: Syntax,
 }
 
 impl FormattingOptions {
@@ -20,16 +57,41 @@ impl FormattingOptions {
         Self::default()
     }
 
-    /// Returns a configurations which prints the output in a compact way
+    /// Returns a configurations which prints the output minified in a single line. The
+    /// formatting whitespace is omitted and whitespace in text is collapsed, except within
+    /// `pre` and `textarea` elements.
     pub fn compact() -> Self {
         Self {
             double_quot: false,
+            expand_boolean_attributes: false,
             new_lines: false,
             max_len: 0,
+            wrap_text: false,
+            comment_padding: true,
             tab_size: 0,
+            inline_elements: Self::default_inline_elements(),
+            attribute_order: AttributeOrder::default(),
+            line_ending: LineEnding::default(),
+            indent: IndentStyle::default(),
+            syntax: Syntax::default(),
         }
     }
 
+    /// The default inline elements, see `Element::INLINE_ELEMENTS`
+    pub fn default_inline_elements() -> HashSet<String> {
+        Element::INLINE_ELEMENTS
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    /// Check if the element name is an inline element, case-insensitive
+    pub fn is_inline(&self, name: &str) -> bool {
+        self.inline_elements
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(name))
+    }
+
     /// Return the defined quotes
     pub fn quotes(&self) -> char {
         match self.double_quot {
@@ -38,6 +100,14 @@ impl FormattingOptions {
         }
     }
 
+    /// write the line ending to the buffer
+    pub fn fmt_new_line<W>(&self, f: &mut W) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        write!(f, "{}", self.line_ending.as_str())
+    }
+
     /// write the depth as tab to the buffer
     pub fn fmt_depth<W>(&self, f: &mut W, depth: usize) -> std::fmt::Result
     where
@@ -50,7 +120,7 @@ impl FormattingOptions {
 
         let tabs = depth / self.tab_size as usize;
         for _ in 0..tabs {
-            write!(f, "\t")?;
+            self.indent.fmt(f)?;
         }
 
         Ok(())
@@ -61,9 +131,170 @@ impl Default for FormattingOptions {
     fn default() -> Self {
         Self {
             double_quot: false,
+            expand_boolean_attributes: false,
             new_lines: true,
             max_len: 60,
+            wrap_text: false,
+            comment_padding: true,
             tab_size: 4,
+            inline_elements: Self::default_inline_elements(),
+            attribute_order: AttributeOrder::default(),
+            line_ending: LineEnding::default(),
+            indent: IndentStyle::default(),
+            syntax: Syntax::default(),
+        }
+    }
+}
+
+/// The order of the attributes in the html output
+#[derive(Debug, Clone, Default)]
+pub enum AttributeOrder {
+    /// The class first, followed by all other attributes in alphabetical order
+    #[default]
+    Alphabetical,
+    /// The class first, followed by all other attributes in the order they are stored, which is
+    /// the source order for parsed elements
+    SourceOrder,
+    /// The listed keys first in the listed order, followed by the class and all other
+    /// attributes in alphabetical order. Ex: `["id", "class"]`
+    Priority(Vec<String>),
+    /// The class first, followed by all other attributes sorted by the comparator of the keys
+    Custom(fn(&str, &str) -> Ordering),
+}
+
+/// The line ending used by the formatting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix line ending `\n`
+    #[default]
+    Lf,
+    /// Windows line ending `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    /// Returns the characters of the line ending
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// The syntax of the void elements in the html output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// The html void elements are written without slash like `<br>`, other void elements like
+    /// svg elements or custom elements stay self-closing like `<path/>`
+    #[default]
+    Html5,
+    /// All void elements are self-closing with a space before the slash like `<br />`
+    Xhtml,
+}
+
+/// The whitespace used for one indentation level of the formatting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// One tab `\t`
+    #[default]
+    Tab,
+    /// The amount of spaces, Ex: `Spaces(2)`
+    Spaces(u8),
+}
+
+impl IndentStyle {
+    /// write one indentation level to the buffer
+    pub fn fmt<W>(&self, f: &mut W) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        match self {
+            IndentStyle::Tab => write!(f, "\t"),
+            IndentStyle::Spaces(n) => write!(f, "{:1$}", "", *n as usize),
+        }
+    }
+}
+
+/// Options to control how the html is parsed
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Keep the comments as `Node::Comment` or `Node::ConditionalComment` in the tree, enabled
+    /// by default. When disabled, all comments are removed.
+    pub keep_comments: bool,
+
+    /// Parse the content of conditional comments like `<!--[if IE]><p>IE</p><![endif]-->`
+    /// as a sub-tree, stored as `Node::ConditionalComment`. When disabled, they are kept as
+    /// a normal comment with the content as text.
+    pub conditional_comments: bool,
+
+    /// Decode named and numeric character references like `&amp;` and `&#169;` in text and
+    /// attribute values, enabled by default. Malformed references stay untouched. The content
+    /// of `script` and `style` elements is never decoded. When disabled, the references are
+    /// kept as they are and encoded again when formatting, like `&amp;amp;`.
+    pub decode_entities: bool,
+
+    /// Names of the void elements, case-insensitive. A start tag without end tag of an element
+    /// in this set is parsed as `ElementVariant::Void`, so the following content stays a sibling,
+    /// otherwise the start tag is kept as text. This allows custom void tags like `<x-icon>`.
+    /// The self-closing syntax `<x-icon/>` is always void, an element with an end tag like
+    /// `<x-icon></x-icon>` is always normal and the names of `Element::VOID_ELEMENTS` are
+    /// always void, even when removed from the set.
+    pub void_elements: HashSet<String>,
+
+    /// Collapse every run of ascii whitespace in text nodes to a single space, like css
+    /// `white-space: normal`, disabled by default. The text within `pre` and `textarea`
+    /// elements and the content of `script` and `style` elements is kept as it is.
+    pub collapse_whitespace: bool,
+
+    /// Store the names of the elements and attributes in lowercase, so `<DIV Class="x">` is
+    /// stored like `<div class="x">`. Disabled by default, because the case of custom elements
+    /// like `<Cat/>` matters to some users.
+    pub lowercase_names: bool,
+
+    /// Return an `Error::Parsing` with the position of the first dangling end tag like a stray
+    /// `</div>`, instead of skipping it with a warning. Disabled by default.
+    pub strict: bool,
+
+    /// Return an `Error::Parsing` when a document fragment has more than one element at the top
+    /// level, like single root templates require. Comments and text around the root element
+    /// are allowed. Disabled by default.
+    pub single_root: bool,
+
+    /// The maximum nesting depth of the elements, 256 by default. Deeper input returns an
+    /// `Error::Parsing` instead of overflowing the stack while the tree is built.
+    pub max_depth: usize,
+}
+
+impl ParseOptions {
+    /// The default void elements, see `Element::VOID_ELEMENTS`
+    pub fn default_void_elements() -> HashSet<String> {
+        Element::VOID_ELEMENTS
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    /// Check if the element name is a void element, case-insensitive
+    pub fn is_void(&self, name: &str) -> bool {
+        self.void_elements
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            keep_comments: true,
+            conditional_comments: false,
+            decode_entities: true,
+            void_elements: Self::default_void_elements(),
+            collapse_whitespace: false,
+            lowercase_names: false,
+            strict: false,
+            single_root: false,
+            max_depth: 256,
         }
     }
 }
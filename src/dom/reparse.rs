@@ -0,0 +1,284 @@
+use super::{
+    element::{Element, ElementVariant},
+    node::{shift_depths, shift_source_spans, Node},
+    options::ParseOptions,
+    span::SourceSpan,
+    Dom,
+};
+use crate::{Error, Result};
+use std::ops::Range;
+
+impl<'s> Dom<'s> {
+    /// Update the dom, which was parsed from `old_input`, after the bytes of the `edit` range in
+    /// `old_input` were replaced, resulting in `new_input`. The result is equal to a full parse of
+    /// `new_input` with `Dom::parse`.
+    ///
+    /// Only the deepest element containing the edit is re-parsed and spliced into the tree, the
+    /// source spans of all other elements are moved. When the edit crosses the boundaries of the
+    /// elements, the re-parsed part isn't the same element anymore, the element or one of its
+    /// descendants has no end tag like `<li>one<li>two` or there are warnings, whose positions
+    /// would have to be moved, the whole `new_input` is parsed again. Returns an error when the
+    /// edit range doesn't fit to the inputs.
+    pub fn reparse_range(
+        &mut self,
+        old_input: &str,
+        new_input: &'s str,
+        edit: Range<usize>,
+    ) -> Result<()> {
+        self.reparse_range_with(old_input, new_input, edit, &ParseOptions::default())
+    }
+
+    /// Update the dom, which was parsed from `old_input` with the parse options, after an edit.
+    /// The result is equal to a full parse of `new_input` with `Dom::parse_with`, see
+    /// `Dom::reparse_range`. The whole `new_input` is parsed again as well, when the element
+    /// containing the edit is within a `pre` or `textarea` element, whose whitespace is kept, or
+    /// within foreign content like `svg`.
+    pub fn reparse_range_with(
+        &mut self,
+        old_input: &str,
+        new_input: &'s str,
+        edit: Range<usize>,
+        options: &ParseOptions,
+    ) -> Result<()> {
+        let new_end = (edit.end + new_input.len())
+            .checked_sub(old_input.len())
+            .filter(|end| {
+                edit.start <= edit.end && edit.end <= old_input.len() && edit.start <= *end
+            })
+            .ok_or_else(|| Error::Parsing(format!("Invalid edit range {:?}", edit)))?;
+
+        let unchanged = old_input.get(..edit.start) == new_input.get(..edit.start)
+            && old_input.get(edit.end..) == new_input.get(new_end..);
+        if !unchanged {
+            return Err(Error::Parsing(format!(
+                "The inputs differ outside of the edit range {:?}",
+                edit
+            )));
+        }
+
+        if !self.reparse_element(old_input, new_input, &edit, new_end, options)? {
+            *self = Dom::parse_with(new_input, options)?;
+        }
+        Ok(())
+    }
+
+    /// Re-parse the deepest element containing the edit, returns false when not possible
+    fn reparse_element(
+        &mut self,
+        old_input: &str,
+        new_input: &'s str,
+        edit: &Range<usize>,
+        new_end: usize,
+        options: &ParseOptions,
+    ) -> Result<bool> {
+        if !self.warnings.is_empty() {
+            return Ok(false);
+        }
+        let old_lines = LineIndex::new(old_input);
+        let new_lines = LineIndex::new(new_input);
+
+        let mut path = vec![];
+        let mut range: Option<(&Element, usize, usize)> = None;
+        let mut nodes = &self.children[..];
+        while let Some((index, element, start, end)) =
+            nodes.iter().enumerate().find_map(|(i, n)| {
+                let e = n.element()?;
+                let (start, end) = old_lines.span_range(&e.source_span)?;
+                (start < edit.start && edit.end < end).then_some((i, e, start, end))
+            })
+        {
+            // the whitespace within an ancestor like `pre` and the void elements of foreign
+            // content are only known by a full parse
+            if range.is_some_and(|(parent, ..)| {
+                parent.matches_any_tag(&["pre", "textarea"]) || parent.is_foreign()
+            }) {
+                return Ok(false);
+            }
+            path.push(index);
+            range = Some((element, start, end));
+            nodes = &element.children;
+        }
+        let (name, start, end) = match range {
+            Some((element, start, end)) if is_self_contained(element) => {
+                (element.name.clone(), start, end)
+            }
+            _ => return Ok(false),
+        };
+
+        // the element has to be parsed again from the start to the end tag, the depth of the
+        // ancestors counts to the maximum depth
+        let text = &new_input[start..end + new_end - edit.end];
+        let options = ParseOptions {
+            max_depth: options.max_depth.saturating_sub(path.len() - 1),
+            ..options.clone()
+        };
+        let element = match Dom::parse_with(text, &options) {
+            Ok(mut dom) if dom.children.len() == 1 && dom.warnings.is_empty() => {
+                match dom.children.pop() {
+                    Some(Node::Element(e))
+                        if e.name == name
+                            && e.source_span.text.len() == text.len()
+                            && is_self_contained(&e) =>
+                    {
+                        e
+                    }
+                    _ => return Ok(false),
+                }
+            }
+            _ => return Ok(false),
+        };
+        let (line, column) = new_lines.line_col(start);
+        let mut node = Node::Element(element);
+        shift_source_spans(std::slice::from_mut(&mut node), line, column);
+        shift_depths(std::slice::from_mut(&mut node), path.len() - 1);
+
+        // move the spans of all other elements, the ancestors contain the edit
+        let mover = SpanMover {
+            old_lines,
+            new_lines,
+            edit: edit.clone(),
+            new_end,
+        };
+        let mut nodes = &mut self.children[..];
+        for (depth, index) in path.iter().enumerate() {
+            mover.move_spans(&mut nodes[..*index]);
+            mover.move_spans(&mut nodes[index + 1..]);
+            if depth + 1 == path.len() {
+                nodes[*index] = node;
+                break;
+            }
+            let parent = &mut nodes[*index];
+            if let Node::Element(e) = parent {
+                mover.move_span(e);
+            }
+            nodes = parent.child_nodes_mut();
+        }
+        Ok(true)
+    }
+}
+
+/// Check that the element and all its descendants are parsed the same within any ancestors,
+/// because they end with an end tag or are void by their name or the self-closing syntax. Where
+/// an element with an implied end tag like `<li>` or a start tag without end tag like `<div>`
+/// ends and whether a start tag like `<x-icon>` is kept as text, depends on the following content
+/// and the ancestors, which are only known by a full parse. An unclosed element can end with the
+/// end tag of its last child, like `<b><b>bold</b>`, which isn't its own end tag.
+fn is_self_contained(element: &Element) -> bool {
+    let ended = match element.variant {
+        ElementVariant::Void => {
+            element.matches_any_tag(Element::VOID_ELEMENTS)
+                || element.source_span.text.ends_with("/>")
+        }
+        ElementVariant::RawText => true,
+        ElementVariant::Normal => {
+            let tag = element.source_span.text.trim_end_matches(WSP);
+            let tag = tag
+                .strip_suffix('>')
+                .unwrap_or_default()
+                .trim_end_matches(WSP);
+            let name_start = tag.len().saturating_sub(element.name.len());
+            tag.get(name_start..)
+                .is_some_and(|name| name.eq_ignore_ascii_case(&element.name))
+                && tag[..name_start].trim_end_matches(WSP).ends_with("</")
+                && !matches!(element.children.last(), Some(Node::Element(last))
+                    if last.source_span.end_line == element.source_span.end_line
+                        && last.source_span.end_column == element.source_span.end_column)
+        }
+    };
+    ended
+        && element.children.iter().all(|child| match child {
+            Node::Element(e) => is_self_contained(e),
+            Node::Text(text) if element.variant == ElementVariant::Normal => !text
+                .match_indices('<')
+                .any(|(i, _)| text[i + 1..].starts_with(|c: char| c.is_ascii_alphabetic())),
+            _ => true,
+        })
+}
+
+/// The whitespace allowed within tags, see `WSP` of the grammar
+const WSP: &[char] = &[' ', '\t', '\r', '\n'];
+
+/// Moves the source spans of elements from the old to the new input
+struct SpanMover<'o, 's> {
+    old_lines: LineIndex<'o>,
+    new_lines: LineIndex<'s>,
+    edit: Range<usize>,
+    new_end: usize,
+}
+
+impl<'o, 's> SpanMover<'o, 's> {
+    /// Move the source spans of the nodes and their descendants
+    fn move_spans(&self, nodes: &mut [Node<'s>]) {
+        for node in nodes.iter_mut() {
+            if let Node::Element(e) = node {
+                self.move_span(e);
+            }
+            self.move_spans(node.child_nodes_mut());
+        }
+    }
+
+    fn move_span(&self, element: &mut Element<'s>) {
+        if let Some((start, end)) = self.old_lines.span_range(&element.source_span) {
+            // a span ending at the edit start is before the edit, one starting at the edit end
+            // is after the edit
+            let start = self.shift(start, start >= self.edit.end);
+            let end = self.shift(end, end > self.edit.start);
+            let (start_line, start_column) = self.new_lines.line_col(start);
+            let (end_line, end_column) = self.new_lines.line_col(end);
+            element.source_span = SourceSpan::new(
+                &self.new_lines.input[start..end],
+                start_line,
+                end_line,
+                start_column,
+                end_column,
+            );
+        }
+    }
+
+    fn shift(&self, offset: usize, after_edit: bool) -> usize {
+        match after_edit {
+            true => offset + self.new_end - self.edit.end,
+            false => offset,
+        }
+    }
+}
+
+/// Convert between byte offsets and the lines and columns of the source spans
+struct LineIndex<'i> {
+    input: &'i str,
+    line_starts: Vec<usize>,
+}
+
+impl<'i> LineIndex<'i> {
+    fn new(input: &'i str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { input, line_starts }
+    }
+
+    /// The byte offset of the line and column, both starting at 1
+    fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let rest = &self.input[start..];
+        match rest.char_indices().nth(column.checked_sub(1)?) {
+            Some((i, _)) => Some(start + i),
+            None if rest.chars().count() + 1 == column => Some(self.input.len()),
+            None => None,
+        }
+    }
+
+    /// The byte range of the source span
+    fn span_range(&self, span: &SourceSpan) -> Option<(usize, usize)> {
+        let start = self.offset(span.start_line, span.start_column)?;
+        let end = self.offset(span.end_line, span.end_column)?;
+        (start <= end && self.input.get(start..end) == Some(&span.text[..])).then_some((start, end))
+    }
+
+    /// The line and column of the byte offset, both starting at 1
+    fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let start = self.line_starts[line - 1];
+        (line, self.input[start..offset].chars().count() + 1)
+    }
+}
@@ -0,0 +1,270 @@
+use super::{element::Element, formatting, node::Node};
+use crate::{
+    grammar::{Grammar, Rule},
+    Error, Result,
+};
+use pest::{iterators::Pair, Parser};
+
+/// A parsed css selector, like `div.btn > a[href], #main p`.
+///
+/// Supported are tag names, the universal selector `*`, `#id`, `.class`, `[attr]` and
+/// `[attr=value]` combined into compound selectors, the descendant (` `) and child (`>`)
+/// combinators and selector lists separated by `,`. Tag names and attribute keys are compared
+/// case-insensitive, ids, classes and attribute values case-sensitive. Namespaced names like
+/// `svg:rect` are selected with `svg|rect` or the escaped `svg\:rect`, pseudo-classes like
+/// `:hover` aren't supported.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Selector {
+    alternatives: Vec<ComplexSelector>,
+}
+
+/// Compound selectors joined by combinators, the last compound is the matched element
+#[derive(Debug, Clone, PartialEq)]
+struct ComplexSelector {
+    compounds: Vec<CompoundSelector>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct CompoundSelector {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+}
+
+impl Selector {
+    /// Parse the selector, returns an error for invalid selectors
+    pub fn parse(input: &str) -> Result<Self> {
+        let pairs = match Grammar::parse(Rule::selector, input) {
+            Ok(pairs) => pairs,
+            Err(error) => return Err(formatting::error_msg(error)),
+        };
+
+        let mut alternatives = vec![];
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::selector_complex => alternatives.push(Self::build_complex(pair)?),
+                Rule::EOI => (),
+                _ => return Err(unexpected_rule(&pair)),
+            }
+        }
+        Ok(Self { alternatives })
+    }
+
+    /// Check if the element matches the selector, the ancestors are ordered from the root to
+    /// the parent of the element
+    pub fn matches(&self, element: &Element, ancestors: &[&Element]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| match complex.compounds.split_last() {
+                Some((last, compounds)) => {
+                    last.matches(element)
+                        && matches_ancestors(compounds, &complex.combinators, ancestors)
+                }
+                None => false,
+            })
+    }
+
+    /// Find the index paths of all matching elements in document order
+    pub fn match_paths(&self, nodes: &[Node]) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        walk_elements(
+            nodes,
+            &mut vec![],
+            &mut vec![],
+            &mut |element, ancestors, path| {
+                if self.matches(element, ancestors) {
+                    paths.push(path.to_vec());
+                }
+            },
+        );
+        paths
+    }
+
+    /// Find all matching elements in document order within the nodes. The ancestors are the
+    /// elements around the nodes, ordered from the root to the parent.
+    pub fn select<'a, 's>(
+        &self,
+        nodes: &'a [Node<'s>],
+        ancestors: Vec<&'a Element<'s>>,
+    ) -> Vec<&'a Element<'s>> {
+        let mut elements = vec![];
+        let mut ancestors = ancestors;
+        walk_elements(
+            nodes,
+            &mut ancestors,
+            &mut vec![],
+            &mut |element, ancestors, _| {
+                if self.matches(element, ancestors) {
+                    elements.push(element);
+                }
+            },
+        );
+        elements
+    }
+
+    fn build_complex(pair: Pair<Rule>) -> Result<ComplexSelector> {
+        let mut complex = ComplexSelector {
+            compounds: vec![],
+            combinators: vec![],
+        };
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::selector_compound => complex.compounds.push(Self::build_compound(pair)?),
+                Rule::selector_combinator => {
+                    let combinator = match pair.into_inner().next() {
+                        Some(_) => Combinator::Child,
+                        None => Combinator::Descendant,
+                    };
+                    complex.combinators.push(combinator);
+                }
+                _ => return Err(unexpected_rule(&pair)),
+            }
+        }
+        Ok(complex)
+    }
+
+    fn build_compound(pair: Pair<Rule>) -> Result<CompoundSelector> {
+        let mut compound = CompoundSelector::default();
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::selector_tag => compound.tag = Some(unescape(pair.as_str())),
+                Rule::selector_universal => (),
+                Rule::selector_id => compound.ids.push(unescape(&pair.as_str()[1..])),
+                Rule::selector_class => compound.classes.push(unescape(&pair.as_str()[1..])),
+                Rule::selector_attr => {
+                    let mut inner = pair.into_inner();
+                    let key = inner.next().map(|k| unescape(k.as_str()));
+                    let value = inner.next().map(|v| match v.as_rule() {
+                        Rule::attr_quoted => v.into_inner().as_str().to_string(),
+                        _ => unescape(v.as_str()),
+                    });
+                    if let Some(key) = key {
+                        compound.attributes.push((key, value));
+                    }
+                }
+                Rule::selector_pseudo => {
+                    return Err(Error::Parsing(format!(
+                        "The pseudo-class {} of the selector isn't supported",
+                        pair.as_str()
+                    )))
+                }
+                _ => return Err(unexpected_rule(&pair)),
+            }
+        }
+        Ok(compound)
+    }
+}
+
+impl CompoundSelector {
+    fn matches(&self, element: &Element) -> bool {
+        if let Some(tag) = &self.tag {
+            if !element.name.eq_ignore_ascii_case(tag) {
+                return false;
+            }
+        }
+
+        if !self.ids.iter().all(|id| element.id() == Some(id.as_str())) {
+            return false;
+        }
+
+        if !self.classes.iter().all(|c| element.has_class(c)) {
+            return false;
+        }
+
+        self.attributes.iter().all(|(key, value)| {
+            if key.eq_ignore_ascii_case("class") {
+                let classes = element.classes.join(" ");
+                return match value {
+                    Some(value) => &classes == value,
+                    None => !element.classes.is_empty(),
+                };
+            }
+            match value {
+                Some(value) => element.get_attribute(key) == Some(value.as_str()),
+                None => element.has_attribute(key),
+            }
+        })
+    }
+}
+
+/// Match the compounds from right to left against the ancestors, from the closest ancestor to
+/// the root. Descendant combinators try every possible ancestor.
+fn matches_ancestors(
+    compounds: &[CompoundSelector],
+    combinators: &[Combinator],
+    ancestors: &[&Element],
+) -> bool {
+    let (compound, compounds) = match compounds.split_last() {
+        Some(split) => split,
+        None => return true,
+    };
+    let (combinator, combinators) = match combinators.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    match combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, ancestors)) => {
+                compound.matches(parent) && matches_ancestors(compounds, combinators, ancestors)
+            }
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|i| {
+            compound.matches(ancestors[i])
+                && matches_ancestors(compounds, combinators, &ancestors[..i])
+        }),
+    }
+}
+
+/// Call the function for every element in document order, together with its ancestors and the
+/// index path from the root nodes to the element
+fn walk_elements<'a, 's, F>(
+    nodes: &'a [Node<'s>],
+    ancestors: &mut Vec<&'a Element<'s>>,
+    path: &mut Vec<usize>,
+    f: &mut F,
+) where
+    F: FnMut(&'a Element<'s>, &[&'a Element<'s>], &[usize]),
+{
+    for (index, node) in nodes.iter().enumerate() {
+        if let Node::Element(element) = node {
+            path.push(index);
+            f(element, ancestors, path);
+            ancestors.push(element);
+            walk_elements(&element.children, ancestors, path, f);
+            ancestors.pop();
+            path.pop();
+        }
+    }
+}
+
+/// Remove the backslashes of escaped characters and replace the `|` of a namespace prefix like
+/// `svg|rect` with the `:` of the element name `svg:rect`
+fn unescape(ident: &str) -> String {
+    let mut result = String::with_capacity(ident.len());
+    let mut chars = ident.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            '|' => result.push(':'),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn unexpected_rule(pair: &Pair<Rule>) -> Error {
+    Error::Parsing(format!(
+        "Failed to parse selector at rule: {:?}",
+        pair.as_rule()
+    ))
+}
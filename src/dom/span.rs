@@ -1,10 +1,11 @@
 use serde::Serialize;
+use std::borrow::Cow;
 
 /// Span of the information in the parsed source.
 #[derive(Debug, Default, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SourceSpan<'s> {
-    pub text: &'s str,
+    pub text: Cow<'s, str>,
     pub start_line: usize,
     pub end_line: usize,
     pub start_column: usize,
@@ -20,11 +21,34 @@ impl<'s> SourceSpan<'s> {
         end_column: usize,
     ) -> Self {
         Self {
-            text,
+            text: Cow::Borrowed(text),
             start_line,
             end_line,
             start_column,
             end_column,
         }
     }
+
+    /// Convert the span into an owned span, which doesn't borrow from the source. Unlike
+    /// `SourceSpan::deep_clone_owned` an already owned text isn't copied.
+    pub fn into_owned(self) -> SourceSpan<'static> {
+        SourceSpan {
+            text: Cow::Owned(self.text.into_owned()),
+            start_line: self.start_line,
+            end_line: self.end_line,
+            start_column: self.start_column,
+            end_column: self.end_column,
+        }
+    }
+
+    /// Clone the span into an owned span, which doesn't borrow from the source
+    pub fn deep_clone_owned(&self) -> SourceSpan<'static> {
+        SourceSpan {
+            text: Cow::Owned(self.text.to_string()),
+            start_line: self.start_line,
+            end_line: self.end_line,
+            start_column: self.start_column,
+            end_column: self.end_column,
+        }
+    }
 }
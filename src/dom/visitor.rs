@@ -0,0 +1,61 @@
+use super::element::Element;
+use super::node::Node;
+
+/// A read-only walk over the nodes in document order, see `Dom::walk` and `Node::walk`.
+/// Every node is visited before its children, the depth of the starting nodes is `0`.
+/// All methods do nothing by default, so only the needed ones have to be implemented.
+pub trait Visitor {
+    /// Visit an element, its children are visited next with `depth + 1`
+    fn visit_element(&mut self, _element: &Element, _depth: usize) {}
+
+    /// Visit a text node
+    fn visit_text(&mut self, _text: &str, _depth: usize) {}
+
+    /// Visit a comment
+    fn visit_comment(&mut self, _comment: &str, _depth: usize) {}
+
+    /// Visit the content of a CDATA section
+    fn visit_cdata(&mut self, _data: &str, _depth: usize) {}
+
+    /// Visit a processing instruction like the xml declaration, including `<?` and `?>`
+    fn visit_processing_instruction(&mut self, _instruction: &str, _depth: usize) {}
+
+    /// Visit a conditional comment, its children are visited next with `depth + 1`
+    fn visit_conditional_comment(&mut self, _condition: &str, _depth: usize) {}
+}
+
+/// Visit the nodes and their descendants in document order
+pub(super) fn walk_nodes<V>(nodes: &[Node], visitor: &mut V, depth: usize)
+where
+    V: Visitor + ?Sized,
+{
+    for node in nodes {
+        walk_node(node, visitor, depth);
+    }
+}
+
+/// Visit the node and its descendants in document order
+pub(super) fn walk_node<V>(node: &Node, visitor: &mut V, depth: usize)
+where
+    V: Visitor + ?Sized,
+{
+    match node {
+        Node::Element(element) => {
+            visitor.visit_element(element, depth);
+            walk_nodes(&element.children, visitor, depth + 1);
+        }
+        Node::Text(text) => visitor.visit_text(text, depth),
+        Node::Comment(comment) => visitor.visit_comment(comment, depth),
+        Node::CData(data) => visitor.visit_cdata(data, depth),
+        Node::ProcessingInstruction(instruction) => {
+            visitor.visit_processing_instruction(instruction, depth)
+        }
+        Node::ConditionalComment {
+            condition,
+            children,
+        } => {
+            visitor.visit_conditional_comment(condition, depth);
+            walk_nodes(children, visitor, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Error;
+use std::fmt::Display;
+
+/// A warning of the parsing, the invalid part of the html was skipped or kept as it is.
+/// The line and column are the start position in the parsed source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// An end tag without start tag was skipped, Ex: `</div>`
+    DanglingElement {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    /// An attribute which was already declared on the element was skipped, the first value is kept
+    DuplicateAttribute {
+        name: String,
+        element: String,
+        line: usize,
+        column: usize,
+    },
+    /// An attribute which couldn't be parsed was skipped
+    AttributeParseError {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// Any other part which couldn't be parsed, like the content of a conditional comment
+    ParseError {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl Warning {
+    pub(crate) fn parse_error(error: Error, line: usize, column: usize) -> Self {
+        Warning::ParseError {
+            message: error.to_string(),
+            line,
+            column,
+        }
+    }
+
+    /// Returns the line and column of the start position in the source
+    pub fn line_col(&self) -> (usize, usize) {
+        match self {
+            Warning::DanglingElement { line, column, .. }
+            | Warning::DuplicateAttribute { line, column, .. }
+            | Warning::AttributeParseError { line, column, .. }
+            | Warning::ParseError { line, column, .. } => (*line, *column),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::DanglingElement { name, line, .. } => {
+                write!(f, "Dangling end tag `</{}>` at line {} skipped", name, line)
+            }
+            Warning::DuplicateAttribute {
+                name,
+                element,
+                line,
+                ..
+            } => write!(
+                f,
+                "Duplicate attribute `{}` on element `{}` at line {}, the first value is kept",
+                name, element, line
+            ),
+            Warning::AttributeParseError { message, .. } | Warning::ParseError { message, .. } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
@@ -4,10 +4,23 @@ use thiserror::Error as ThisError;
 pub enum Error {
     #[error("{0}")]
     Parsing(String),
+    /// The input doesn't match the grammar, the position is the 1-based line and column
+    #[error("{message} at line {line}, column {column}")]
+    ParsingAt {
+        message: String,
+        line: usize,
+        column: usize,
+    },
     #[error("{0}")]
     Cli(String),
+    /// An invalid change of the dom, like adding children to a void element
+    #[error("{0}")]
+    Manipulation(String),
     #[error("{0}")]
     IO(#[from] std::io::Error),
+    /// The input bytes aren't valid utf-8, see `Dom::parse_bytes`
+    #[error("{0}")]
+    Utf8(#[from] std::str::Utf8Error),
     #[error("{0}")]
     Serde(#[from] serde_json::Error),
 }
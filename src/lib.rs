@@ -11,10 +11,13 @@
 //! - Parse html-fragments
 //! - Parse empty documents
 //! - Parse with the same api for both documents and fragments
-//! - Parse custom, non-standard, elements; `<cat/>`, `<Cat/>` and `<C4-t/>`
+//! - Parse custom, non-standard, elements; `<cat/>`, `<Cat/>`, `<C4-t/>`, `<my.widget/>` and `<x_y/>`.
+//!   Element names start with an ascii letter, followed by ascii letters, digits, `-`, `_`, `.`, `:`
+//!   or the non ascii characters allowed for custom elements
 //! - Removes comments
 //! - Removes dangling elements
 //! - Iterate over all nodes in the dom three
+//! - Query elements with css selectors like `nav > a.btn[href]`
 //!
 //! ## Examples
 //!
@@ -112,9 +115,16 @@ mod grammar;
 
 use grammar::Rule;
 
-pub use crate::dom::element::{Element, ElementVariant};
-pub use crate::dom::node::Node;
-pub use crate::dom::options::FormattingOptions;
+pub use crate::dom::builder::DomBuilder;
+pub use crate::dom::element::{Attributes, Dir, Element, ElementVariant};
+pub use crate::dom::event::Event;
+pub use crate::dom::node::{Node, NodeIntoIterator};
+pub use crate::dom::options::{
+    AttributeOrder, FormattingOptions, IndentStyle, LineEnding, ParseOptions, Syntax,
+};
+pub use crate::dom::parse_attributes;
+pub use crate::dom::visitor::Visitor;
+pub use crate::dom::warning::Warning;
 pub use crate::dom::Dom;
 pub use crate::dom::DomVariant;
 pub use crate::error::Error;
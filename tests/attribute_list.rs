@@ -0,0 +1,37 @@
+use lithtml::{parse_attributes, Result};
+
+#[test]
+fn it_can_parse_attribute_list() -> Result<()> {
+    let (classes, attributes) = parse_attributes(r#"class="a  b" data-x='1' id=main hidden"#)?;
+    assert_eq!(classes, ["a", "b"]);
+    assert_eq!(attributes.len(), 3);
+    assert_eq!(attributes["data-x"].as_deref(), Some("1"));
+    assert_eq!(attributes["id"].as_deref(), Some("main"));
+    assert_eq!(attributes["hidden"], None);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_empty_attribute_list() -> Result<()> {
+    let (classes, attributes) = parse_attributes("")?;
+    assert!(classes.is_empty() && attributes.is_empty());
+    let (classes, attributes) = parse_attributes("   ")?;
+    assert!(classes.is_empty() && attributes.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_first_duplicate_attribute() -> Result<()> {
+    let (classes, attributes) =
+        parse_attributes(r#"href="first" class="a a" href="second" CLASS="b""#)?;
+    assert_eq!(attributes.len(), 1);
+    assert_eq!(attributes["href"].as_deref(), Some("first"));
+    assert_eq!(classes, ["a"]);
+    Ok(())
+}
+
+#[test]
+fn it_errors_on_malformed_attribute_list() {
+    assert!(parse_attributes(r#"class="a" <div>"#).is_err());
+    assert!(parse_attributes(r#"="value""#).is_err());
+}
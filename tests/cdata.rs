@@ -0,0 +1,50 @@
+use lithtml::{Dom, FormattingOptions, Node, Result};
+
+#[test]
+fn it_can_parse_cdata_inside_an_element() -> Result<()> {
+    let html = "<svg><script><![CDATA[ if (a < b && c) {} ]]></script><text><![CDATA[a < b]]></text></svg>";
+    let dom = Dom::parse(html)?;
+    let svg = dom.children[0].element().unwrap();
+    let text = svg.children[1].element().unwrap();
+    assert_eq!(text.children, vec![Node::CData("a < b".into())]);
+    assert_eq!(text.children[0].cdata(), Some("a < b"));
+    assert_eq!(text.text_content(), "a < b");
+    Ok(())
+}
+
+#[test]
+fn it_ends_cdata_at_the_first_end_marker() -> Result<()> {
+    let dom = Dom::parse("<p><![CDATA[a]]b]>c]]>]]></p>")?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0].cdata(), Some("a]]b]>c"));
+    assert_eq!(p.children[1].text(), Some("]]>"));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_content_of_cdata_verbatim() -> Result<()> {
+    let dom = Dom::parse("<p><![CDATA[&amp; <b>not bold</b>\n  x]]></p>")?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0].cdata(), Some("&amp; <b>not bold</b>\n  x"));
+    Ok(())
+}
+
+#[test]
+fn it_can_round_trip_cdata() -> Result<()> {
+    let html = "<p>a <![CDATA[x < y]]> b</p>";
+    let dom = Dom::parse(html)?;
+    let mut output = String::new();
+    dom.fmt_opt(&mut output, &FormattingOptions::compact())
+        .unwrap();
+    assert_eq!(output, html);
+    assert!(Dom::parse(&dom.to_string())?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_cdata_at_the_top_level() -> Result<()> {
+    let dom = Dom::parse("<![CDATA[<x>]]>")?;
+    assert_eq!(dom.children, vec![Node::CData("<x>".into())]);
+    assert_eq!(dom.to_string(), "<![CDATA[<x>]]>\n");
+    Ok(())
+}
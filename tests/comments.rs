@@ -1,5 +1,6 @@
+use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, DomVariant, FormattingOptions, Node, ParseOptions, Result};
 
 #[test]
 fn it_can_parse_document_with_just_one_comment() -> Result<()> {
@@ -15,3 +16,147 @@ fn it_can_parse_document_with_just_comments() -> Result<()> {
     assert_json_snapshot!(ast);
     Ok(())
 }
+
+const CONDITIONAL: &str = indoc!(
+    r#"<div>
+        <!--[if lt IE 9]>
+            <link rel="stylesheet" href="ie.css">
+            <p class="ie">Old browser</p>
+        <![endif]-->
+    </div>"#
+);
+
+#[test]
+fn it_keeps_conditional_comments_opaque_by_default() -> Result<()> {
+    let dom = Dom::parse(CONDITIONAL)?;
+    let div = dom.children[0].element().unwrap();
+    assert!(div.children[0]
+        .comment()
+        .unwrap()
+        .contains("<p class=\"ie\">"));
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_conditional_comments_as_sub_tree() -> Result<()> {
+    let options = ParseOptions {
+        conditional_comments: true,
+        ..ParseOptions::default()
+    };
+    let dom = Dom::parse_with(CONDITIONAL, &options)?;
+    let div = dom.children[0].element().unwrap();
+    match &div.children[0] {
+        Node::ConditionalComment {
+            condition,
+            children,
+        } => {
+            assert_eq!(condition, "lt IE 9");
+            assert_eq!(children.len(), 2);
+            let p = children[1].element().unwrap();
+            assert_eq!(p.name, "p");
+            assert_eq!(p.source_span.start_line, 4);
+            assert_eq!(p.source_span.start_column, 9);
+        }
+        node => panic!("expected a conditional comment, got {:?}", node),
+    }
+
+    // the conditional comment is written back and parsed the same way
+    let html = dom.to_string();
+    assert!(html.contains("<!--[if lt IE 9]>"));
+    assert!(Dom::parse_with(&html, &options)?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_can_remove_comments_while_parsing() -> Result<()> {
+    let html = indoc!(
+        "<!-- leading -->
+        <div>
+            <!-- inside -->
+            <p>Text<!-- inline --></p>
+            <!--[if IE]><p>IE</p><![endif]-->
+        </div>
+        <!-- trailing -->"
+    );
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.children.len(), 3);
+    assert_eq!(dom.children[1].element().unwrap().children.len(), 3);
+
+    let options = ParseOptions {
+        keep_comments: false,
+        ..ParseOptions::default()
+    };
+    let dom = Dom::parse_with(html, &options)?;
+    assert_eq!(dom.to_string(), "<div>\n\t<p>Text</p>\n</div>\n");
+    let nodes = Node::parse_with("<!-- a --><b>text</b><!-- b -->", &options)?;
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].to_string(), "<b>text</b>");
+    assert!(Dom::parse_with("<!-- only -->", &options)?
+        .children
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_writes_comments_with_padding_by_default() -> Result<()> {
+    let dom = Dom::parse("<!-- padded --><div><!--unpadded--></div>")?;
+    assert_eq!(
+        dom.to_string(),
+        "<!-- padded -->\n<div>\n\t<!-- unpadded -->\n</div>\n"
+    );
+    assert!(Dom::parse(&dom.to_string())?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_can_write_comments_without_padding() -> Result<()> {
+    let source = "<!-- padded --><div><!--unpadded--><!--\n\tmulti\n\tline\n--></div>";
+    let dom = Dom::parse(source)?;
+    assert_eq!(dom.children[0].comment(), Some(" padded "));
+    let o = FormattingOptions {
+        comment_padding: false,
+        ..FormattingOptions::compact()
+    };
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &o).unwrap();
+    assert_eq!(html, source);
+    assert_eq!(Dom::parse(&html)?, dom);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_a_conditional_comment_at_the_top_level() -> Result<()> {
+    let options = ParseOptions {
+        conditional_comments: true,
+        ..ParseOptions::default()
+    };
+    let mut dom = Dom::parse_with("<!--[if IE]><p>IE</p><![endif]-->", &options)?;
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    assert!(matches!(
+        &dom.children[..],
+        [Node::ConditionalComment { condition, .. }] if condition == "IE"
+    ));
+
+    dom.validate()?;
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    Ok(())
+}
+
+#[test]
+fn it_keeps_conditional_comments_verbatim() -> Result<()> {
+    let html =
+        r#"<!--[if lt IE 9]><p class="ie">a > b</p><script src="ie.js"></script><![endif]-->"#;
+    let dom = Dom::parse(html)?;
+    assert_eq!(
+        dom.children[0].comment(),
+        Some(r#"[if lt IE 9]><p class="ie">a > b</p><script src="ie.js"></script><![endif]"#)
+    );
+    assert_eq!(dom.to_string(), format!("{html}\n"));
+
+    let mut compact = String::new();
+    dom.fmt_opt(&mut compact, &FormattingOptions::compact())
+        .unwrap();
+    assert_eq!(compact, html);
+    assert_eq!(Dom::parse(&compact)?, dom);
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use indoc::indoc;
 use insta::assert_snapshot;
-use lithtml::{Dom, Node, Result};
+use lithtml::{Dom, Element, ElementVariant, Node, Result};
 
 #[test]
 fn it_can_create_artefacts() -> Result<()> {
@@ -35,3 +35,35 @@ fn it_can_create_artefacts() -> Result<()> {
     assert_snapshot!(dom);
     Ok(())
 }
+
+#[test]
+fn it_can_convert_into_nodes() -> Result<()> {
+    let mut element = Element {
+        name: "p".into(),
+        variant: ElementVariant::Normal,
+        ..Element::default()
+    };
+    element.children.push("Hello".into());
+
+    let mut dom = Dom::new();
+    dom.children.push(element.into());
+
+    assert_eq!(dom.to_string(), Dom::parse("<p>Hello</p>")?.to_string());
+    Ok(())
+}
+
+#[test]
+fn it_can_create_element_nodes() -> Result<()> {
+    let element = Element {
+        name: "b".into(),
+        variant: ElementVariant::Normal,
+        children: vec![Node::from("bold")],
+        ..Element::default()
+    };
+    let node = Node::new_element(element.clone());
+    assert_eq!(node, Node::from(element));
+    assert_eq!(node.to_string(), "<b>bold</b>");
+    assert_eq!(Node::from("a & b").to_string(), "a &amp; b");
+    assert_eq!(Node::new_comment("note").to_string(), "<!-- note -->");
+    Ok(())
+}
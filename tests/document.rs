@@ -1,6 +1,6 @@
 use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, DomVariant, Element, Error, Result};
 
 #[test]
 fn it_can_parse_minimal_document() -> Result<()> {
@@ -34,3 +34,137 @@ fn it_error_when_doctype_and_multiple_html() {
     let html = "<!DOCTYPE html><html></html><html></html>";
     assert!(Dom::parse(html).is_err());
 }
+
+#[test]
+fn it_reports_the_position_of_parse_errors() {
+    let html = "<div>\n    <p>Text</p>\n    <!-- open comment\n</div>";
+    let error = Dom::parse(html).unwrap_err();
+    match &error {
+        Error::ParsingAt {
+            message,
+            line,
+            column,
+        } => {
+            assert_eq!((*line, *column), (3, 6));
+            assert!(message.starts_with("expected element name"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    let display = error.to_string();
+    assert!(!display.contains('\n'));
+    assert!(display.ends_with("at line 3, column 6"));
+}
+
+#[test]
+fn it_can_force_a_document() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <!-- comment -->
+        <!DOCTYPE html>
+        <html><body><p>Text</p></body></html>
+        <!-- trailing -->
+    "#
+    );
+    let dom = Dom::parse_document(html)?;
+    assert_eq!(dom.tree_type, DomVariant::Document);
+    assert_eq!(dom, Dom::parse(html)?);
+    assert_eq!(
+        Dom::parse_document("<HTML></HTML>")?.tree_type,
+        DomVariant::Document
+    );
+    Ok(())
+}
+
+#[test]
+fn it_rejects_a_forced_document_without_document_structure() {
+    assert!(Dom::parse_document("").is_err());
+    assert!(Dom::parse_document("<!DOCTYPE html>").is_err());
+    assert!(Dom::parse_document("<div></div>").is_err());
+    assert!(Dom::parse_document("<html></html><div></div>").is_err());
+    assert!(Dom::parse_document("<html></html>text").is_err());
+    assert!(Dom::parse_document("<html></html><html></html>").is_err());
+}
+
+#[test]
+fn it_keeps_the_doctype_when_formatting() -> Result<()> {
+    let doctypes = [
+        "<!doctype html>",
+        r#"<!DOCTYPE html PUBLIC "-//W3C//DTD HTML 4.01//EN" "http://www.w3.org/TR/html4/strict.dtd">"#,
+        r#"<!DOCTYPE html SYSTEM "about:legacy-compat">"#,
+    ];
+    for doctype in doctypes {
+        let html = format!("{}\n<html><body><p>Text</p></body></html>", doctype);
+        let dom = Dom::parse(&html)?;
+        assert_eq!(dom.doctype.as_deref(), Some(doctype));
+
+        let output = dom.to_string();
+        assert!(output.starts_with(&format!("{}\n<html>", doctype)));
+        let reparsed = Dom::parse(&output)?;
+        assert_eq!(reparsed.doctype.as_deref(), Some(doctype));
+        assert_eq!(reparsed.tree_type, DomVariant::Document);
+    }
+    assert_eq!(Dom::parse("<html></html>")?.doctype, None);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_bytes_with_a_byte_order_mark() -> Result<()> {
+    let html = "<!DOCTYPE html><html><body><p>Grüße</p></body></html>";
+    let bytes = [b"\xEF\xBB\xBF".as_slice(), html.as_bytes()].concat();
+    let dom = Dom::parse_bytes(&bytes)?;
+    assert_eq!(dom.tree_type, DomVariant::Document);
+    assert_eq!(dom, Dom::parse(html)?);
+    assert_eq!(Dom::parse_bytes(html.as_bytes())?, dom);
+    Ok(())
+}
+
+#[test]
+fn it_errors_on_invalid_utf8_bytes() {
+    let error = Dom::parse_bytes(b"<p>\xFF\xFE</p>").unwrap_err();
+    assert!(matches!(error, Error::Utf8(_)));
+}
+
+#[test]
+fn it_writes_the_xml_declaration_before_the_doctype() -> Result<()> {
+    let html = "<?xml version=\"1.0\"?><!DOCTYPE html><html><body></body></html>";
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.tree_type, DomVariant::Document);
+    assert_eq!(dom.children[0].to_string(), "<?xml version=\"1.0\"?>");
+    assert!(dom
+        .to_string()
+        .starts_with("<?xml version=\"1.0\"?>\n<!DOCTYPE html>\n<html>"));
+    Ok(())
+}
+
+#[test]
+fn it_validates_a_manually_changed_document() -> Result<()> {
+    let mut dom = Dom::parse("<!DOCTYPE html><html><body></body></html>")?;
+    dom.validate()?;
+    assert_eq!(dom.tree_type, DomVariant::Document);
+
+    let html = dom.children[0].clone();
+    dom.children.push(html);
+    assert!(matches!(dom.validate(), Err(Error::Parsing(_))));
+    Ok(())
+}
+
+#[test]
+fn it_validates_a_manually_changed_fragment() -> Result<()> {
+    let mut dom = Dom::parse("<p>a</p>")?;
+    let head = Element {
+        name: "head".into(),
+        ..Element::default()
+    };
+    dom.children.push(head.into());
+    assert!(matches!(dom.validate(), Err(Error::Parsing(_))));
+
+    // a fragment with only an html element is a document
+    dom.children = Dom::parse("<html></html>")?.children;
+    dom.validate()?;
+    assert_eq!(dom.tree_type, DomVariant::Document);
+
+    dom.children.clear();
+    dom.validate()?;
+    assert_eq!(dom.tree_type, DomVariant::Empty);
+    Ok(())
+}
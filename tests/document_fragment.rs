@@ -1,5 +1,6 @@
+use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, DomVariant, Error, ParseOptions, Result};
 
 #[test]
 fn it_can_parse_single_div_as_fragment() -> Result<()> {
@@ -37,3 +38,79 @@ fn it_error_when_html_is_used_in_fragment_root() {
     let html = "<div></div><html></html>";
     assert!(Dom::parse(html).is_err());
 }
+#[test]
+fn it_can_force_a_fragment() -> Result<()> {
+    let dom = Dom::parse_fragment("<html><p>Partial</p></html>")?;
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    assert_eq!(
+        Dom::parse("<html><p>Partial</p></html>")?.tree_type,
+        DomVariant::Document
+    );
+
+    let dom = Dom::parse_fragment("<div></div><html></html>text<!-- comment -->")?;
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    assert_eq!(dom.children.len(), 4);
+
+    assert_eq!(
+        Dom::parse_fragment("")?.tree_type,
+        DomVariant::DocumentFragment
+    );
+    assert_eq!(
+        Dom::parse_fragment("<!-- only -->")?.tree_type,
+        DomVariant::DocumentFragment
+    );
+    Ok(())
+}
+#[test]
+fn it_rejects_document_structure_in_a_forced_fragment() {
+    assert!(Dom::parse_fragment("<!DOCTYPE html><div></div>").is_err());
+    assert!(Dom::parse_fragment("<div></div><head></head>").is_err());
+    assert!(Dom::parse_fragment("<BODY></BODY>").is_err());
+}
+#[test]
+fn it_rejects_head_and_body_of_any_case_in_a_fragment() {
+    assert!(Dom::parse("<div></div><head></head>").is_err());
+    assert!(Dom::parse("<div></div><HEAD></HEAD>").is_err());
+    assert!(Dom::parse("<p></p><Body></Body>").is_err());
+}
+
+#[test]
+fn it_accepts_a_single_root_fragment_with_comments() -> Result<()> {
+    let options = ParseOptions {
+        single_root: true,
+        ..ParseOptions::default()
+    };
+    let html = indoc!(
+        "
+        <!-- template -->
+        <div>
+            <p>a</p>
+            <p>b</p>
+        </div>
+        <!-- end -->
+    "
+    );
+    let dom = Dom::parse_with(html, &options)?;
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    assert_eq!(dom.children.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn it_rejects_a_fragment_with_two_roots_when_single_root() -> Result<()> {
+    let options = ParseOptions {
+        single_root: true,
+        ..ParseOptions::default()
+    };
+    let html = "<div>a</div>\n<div>b</div>";
+    assert!(matches!(
+        Dom::parse_with(html, &options),
+        Err(Error::Parsing(_))
+    ));
+    assert!(matches!(
+        Dom::parse_fragment_with(html, &options),
+        Err(Error::Parsing(_))
+    ));
+    assert!(Dom::parse(html).is_ok());
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use indoc::indoc;
+use lithtml::{Dom, Node, Result};
+
+#[test]
+fn it_equals_ignoring_whitespace_and_spans() -> Result<()> {
+    let a = Dom::parse(indoc!(
+        "<div id='a' title='b'>
+            <p>Hello   world</p>
+        </div>"
+    ))?;
+    let b = Dom::parse("<div title='b' id='a'><p> Hello world </p></div>")?;
+    assert_ne!(a, b);
+    assert!(a.equals_ignoring_whitespace(&b));
+    Ok(())
+}
+
+#[test]
+fn it_ignores_whitespace_only_text_nodes() -> Result<()> {
+    let a = Dom::parse("<div><p>text</p></div>")?;
+    let mut b = Dom::parse("<div><p>text</p></div>")?;
+    if let Some(Node::Element(div)) = b.children.get_mut(0) {
+        div.children.push(Node::new_text("\n    "));
+    }
+    assert!(a.equals_ignoring_whitespace(&b));
+    Ok(())
+}
+
+#[test]
+fn it_does_not_equal_on_significant_differences() -> Result<()> {
+    let a = Dom::parse("<div class='a b'><p>Hello world</p></div>")?;
+    assert!(!a.equals_ignoring_whitespace(&Dom::parse("<div class='a b'><p>Helloworld</p></div>")?));
+    assert!(
+        !a.equals_ignoring_whitespace(&Dom::parse("<div class='b a'><p>Hello world</p></div>")?)
+    );
+    assert!(
+        !a.equals_ignoring_whitespace(&Dom::parse("<div class='a b'><b>Hello world</b></div>")?)
+    );
+    assert!(!a.equals_ignoring_whitespace(&Dom::parse("<div class='a b'></div>")?));
+    Ok(())
+}
+
+#[test]
+fn it_can_output_canonical_json() -> Result<()> {
+    let a = Dom::parse(indoc!(
+        "<div id='a' title='b' class='x y'>
+            <p>Hello   world</p>
+            <!--  note  -->
+        </div>"
+    ))?;
+    let b =
+        Dom::parse("<div title='b' class='x y' id='a'><p> Hello world </p><!-- note --></div>")?;
+    assert_eq!(a.to_canonical_json()?, b.to_canonical_json()?);
+    assert_eq!(
+        a.to_canonical_json()?,
+        r#"{"children":[{"attributes":{"id":"a","title":"b"},"children":[{"children":["Hello world"],"name":"p","variant":"normal"},"note"],"classes":["x","y"],"name":"div","variant":"normal"}],"treeType":"documentFragment"}"#
+    );
+
+    let c = Dom::parse("<div title='b' class='x y' id='a'><p>Hello world!</p></div>")?;
+    assert_ne!(a.to_canonical_json()?, c.to_canonical_json()?);
+    Ok(())
+}
+
+#[test]
+fn it_equals_elements_parsed_at_different_positions() -> Result<()> {
+    let a = Dom::parse("<div class='a'><p>Hello</p></div>")?;
+    let b = Dom::parse("<span></span>\n  <div class='a'><p>Hello</p></div>")?;
+    let (a, b) = (&a.children[0], &b.children[1]);
+    assert_ne!(
+        a.element().unwrap().source_span,
+        b.element().unwrap().source_span
+    );
+    assert_eq!(a, b);
+
+    let c = Dom::parse("<span></span><div class='b'><p>Hello</p></div>")?;
+    assert_ne!(a, &c.children[1]);
+    Ok(())
+}
+
+#[test]
+fn it_is_semantically_equal_with_reordered_classes() -> Result<()> {
+    let a = Dom::parse(indoc!(
+        r#"
+        <div class="b a" id="x">
+            <span class="c d"> text </span>
+        </div>
+    "#
+    ))?;
+    let b = Dom::parse(r#"<div id="x" class="a b"><span class="d c">text</span></div>"#)?;
+    assert_ne!(a, b);
+    assert!(!a.equals_ignoring_whitespace(&b));
+    assert!(a.semantically_eq(&b));
+    Ok(())
+}
+
+#[test]
+fn it_is_not_semantically_equal_with_different_content() -> Result<()> {
+    let a = Dom::parse(r#"<p class="a">Hello   world</p>"#)?;
+    assert!(!a.semantically_eq(&Dom::parse(r#"<p class="a">Hello world</p>"#)?));
+    assert!(!a.semantically_eq(&Dom::parse(r#"<p class="a b">Hello   world</p>"#)?));
+    assert!(!a.semantically_eq(&Dom::parse(r#"<p class="a" id="b">Hello   world</p>"#)?));
+    assert!(a.semantically_eq(&Dom::parse(r#"<p class="a"> Hello   world </p>"#)?));
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use indoc::indoc;
+use lithtml::{Dom, Element, Node, Result};
+
+#[test]
+fn it_can_find_obsolete_elements() -> Result<()> {
+    let html = indoc!(
+        "<CENTER>
+            <font color='red'>Old</font>
+            <p>New <big>text</big></p>
+        </CENTER>"
+    );
+    let dom = Dom::parse(html)?;
+    let obsolete = dom
+        .obsolete_elements()
+        .into_iter()
+        .map(|(e, _)| e.name.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(obsolete, vec!["CENTER", "font", "big"]);
+    assert!(dom.obsolete_elements()[0].1.contains("css"));
+    Ok(())
+}
+
+#[test]
+fn it_finds_no_obsolete_elements_in_modern_html() -> Result<()> {
+    let dom = Dom::parse("<main><p>Modern <strong>html</strong></p></main>")?;
+    assert!(dom.obsolete_elements().is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_find_matching_attributes() -> Result<()> {
+    let html = indoc!(
+        r#"<div style="color: red" onclick="run()">
+            <a href="/" onmouseover="hover()">Home</a>
+            <p style="margin: 0">Text</p>
+        </div>"#
+    );
+    let dom = Dom::parse(html)?;
+
+    let handlers = dom.attributes_matching(|key, _| key.starts_with("on"));
+    let handlers = handlers
+        .iter()
+        .map(|(e, k, v)| (e.name.as_ref(), *k, *v))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        handlers,
+        vec![
+            ("div", "onclick", Some("run()")),
+            ("a", "onmouseover", Some("hover()"))
+        ]
+    );
+
+    let styles = dom.attributes_matching(|key, value| key == "style" && value.is_some());
+    assert_eq!(styles.len(), 2);
+    assert!(dom.attributes_matching(|key, _| key == "id").is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_prune_to_a_selector() -> Result<()> {
+    let html = indoc!(
+        "<header>Navigation</header>
+        <main id='main'>
+            <h1>Title</h1>
+            <article class='post featured'><p>Kept <b>with</b> children</p></article>
+            <article class='post'><p>Dropped</p></article>
+            <section><div><a href='/one' class='btn'>One</a></div></section>
+        </main>
+        <footer><a href='/two'>Two</a></footer>"
+    );
+    let mut dom = Dom::parse(html)?;
+    dom.prune_to("#main article.featured, main a.btn[href]")?;
+    assert_eq!(
+        dom.to_string(),
+        Dom::parse(indoc!(
+            "<main id='main'>
+                <article class='post featured'><p>Kept <b>with</b> children</p></article>
+                <section><div><a href='/one' class='btn'>One</a></div></section>
+            </main>"
+        ))?
+        .to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_prune_to_a_child_selector() -> Result<()> {
+    let mut dom = Dom::parse("<ul><li><a>1</a></li></ul><nav><p><a>2</a></p><a>3</a></nav>")?;
+    dom.prune_to("NAV > a")?;
+    assert_eq!(
+        dom.to_string(),
+        Dom::parse("<nav><a>3</a></nav>")?.to_string()
+    );
+
+    let mut dom = Dom::parse("<p>nothing</p>")?;
+    dom.prune_to("span")?;
+    assert!(dom.children.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_prune_to_an_invalid_selector() -> Result<()> {
+    let mut dom = Dom::parse("<p>text</p>")?;
+    assert!(dom.prune_to("p >").is_err());
+    assert!(dom.prune_to("[href").is_err());
+    assert!(dom.prune_to("").is_err());
+    assert_eq!(dom.children.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn it_can_remove_whitespace_only_text() -> Result<()> {
+    let mut dom =
+        Dom::parse("<ul><li>x</li></ul><p><b>a</b> <i>b</i></p><pre><b>a</b> <i>b</i></pre>")?;
+    if let Node::Element(ul) = &mut dom.children[0] {
+        ul.children.insert(0, Node::new_text("\n  "));
+        ul.children.push(Node::new_text("\n"));
+    }
+    assert_eq!(dom.children[0].element().unwrap().children.len(), 3);
+
+    dom.remove_whitespace_only_text();
+    let ul = dom.children[0].element().unwrap();
+    assert_eq!(ul.children.len(), 1);
+    assert_eq!(
+        ul.children[0].element().unwrap().children[0].text(),
+        Some("x")
+    );
+    assert_eq!(dom.children[1].element().unwrap().children.len(), 2);
+    assert_eq!(dom.children[2].element().unwrap().children.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn it_can_find_elements_with_an_attribute() -> Result<()> {
+    let html = indoc!(
+        r#"<form>
+            <input type="text" name="user" required>
+            <input TYPE="Checkbox" name="remember">
+            <button type="submit" class="btn primary">Send</button>
+        </form>"#
+    );
+    let dom = Dom::parse(html)?;
+    let names = |elements: Vec<&lithtml::Element>| {
+        elements
+            .iter()
+            .map(|e| e.name.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(dom.elements_with_attr("type", None)),
+        ["input", "input", "button"]
+    );
+    assert_eq!(names(dom.elements_with_attr("REQUIRED", None)), ["input"]);
+    assert_eq!(
+        names(dom.elements_with_attr("type", Some("checkbox"))),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        names(dom.elements_with_attr("type", Some("Checkbox"))),
+        ["input"]
+    );
+    assert_eq!(
+        names(dom.elements_with_attr_ignore_case("type", Some("checkbox"))),
+        ["input"]
+    );
+    assert_eq!(
+        names(dom.elements_with_attr("required", Some(""))),
+        Vec::<String>::new()
+    );
+    assert_eq!(names(dom.elements_with_attr("class", None)), ["button"]);
+    assert_eq!(
+        names(dom.elements_with_attr("class", Some("btn primary"))),
+        ["button"]
+    );
+    assert!(dom.elements_with_attr("href", None).is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_find_elements_by_predicate() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <header><h1>Title</h1></header>
+        <main>
+            <h1 id="second">Other</h1>
+            <a href="/a">a</a>
+            <p><a href="/b">b</a><a>c</a></p>
+        </main>"#
+    );
+    let dom = Dom::parse(html)?;
+    let h1 = dom.find_first(|e| e.name == "h1").unwrap();
+    assert_eq!(h1.text_content(), "Title");
+
+    let links = dom.find_all(|e| e.has_attribute("href"));
+    let hrefs: Vec<_> = links
+        .iter()
+        .filter_map(|e| e.get_attribute("href"))
+        .collect();
+    assert_eq!(hrefs, ["/a", "/b"]);
+
+    assert!(dom.find_first(|e| e.name == "table").is_none());
+    assert!(dom.find_all(|e| e.has_class("missing")).is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_get_elements_by_tag_and_class_name() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <DIV class="card big">a</DIV>
+        <section>
+            <div class="card">b</div>
+            <Div class="big">c</Div>
+        </section>
+        <p class="cards">d</p>"#
+    );
+    let dom = Dom::parse(html)?;
+    let texts = |elements: Vec<&Element>| -> Vec<String> {
+        elements.iter().map(|e| e.text_content()).collect()
+    };
+    assert_eq!(texts(dom.get_elements_by_tag_name("div")), ["a", "b", "c"]);
+    assert_eq!(texts(dom.get_elements_by_tag_name("P")), ["d"]);
+    assert!(dom.get_elements_by_tag_name("span").is_empty());
+
+    assert_eq!(texts(dom.get_elements_by_class_name("card")), ["a", "b"]);
+    assert_eq!(texts(dom.get_elements_by_class_name("big")), ["a", "c"]);
+    assert!(dom.get_elements_by_class_name("Card").is_empty());
+    Ok(())
+}
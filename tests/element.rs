@@ -1,6 +1,6 @@
 use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, ElementVariant, Error, Node, ParseOptions, Result, Warning};
 
 #[test]
 fn it_can_parse_one_element() -> Result<()> {
@@ -186,6 +186,78 @@ fn it_skips_dangling_elements() -> Result<()> {
     Ok(())
 }
 #[test]
+fn it_warns_about_dangling_elements() -> Result<()> {
+    let dom = Dom::parse("<div>\n    <p>Text</span></p>\n</div>\n</div>")?;
+    assert_eq!(
+        dom.warnings,
+        [
+            Warning::DanglingElement {
+                name: "span".to_string(),
+                line: 2,
+                column: 12,
+            },
+            Warning::DanglingElement {
+                name: "div".to_string(),
+                line: 4,
+                column: 1,
+            },
+        ]
+    );
+    assert_eq!(dom.warnings[1].line_col(), (4, 1));
+    let json = dom.to_json()?;
+    assert!(Dom::parse_json(&json)?.warnings.is_empty());
+    assert_eq!(
+        dom.warnings_as_strings(),
+        [
+            "Dangling end tag `</span>` at line 2 skipped",
+            "Dangling end tag `</div>` at line 4 skipped"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_warns_about_dangling_elements_in_documents() -> Result<()> {
+    let html = "<!DOCTYPE html>\n<html>\n<body>\n  <p>a</b></p></li>\n</body>\n</html>";
+    let dom = Dom::parse(html)?;
+    assert_eq!(
+        dom.to_string(),
+        Dom::parse(&html.replace("</b>", "").replace("</li>", ""))?.to_string()
+    );
+    let positions: Vec<_> = dom.warnings.iter().map(|w| w.line_col()).collect();
+    assert_eq!(positions, [(4, 7), (4, 15)]);
+    assert_eq!(
+        dom.warnings_as_strings(),
+        [
+            "Dangling end tag `</b>` at line 4 skipped",
+            "Dangling end tag `</li>` at line 4 skipped"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_errors_on_dangling_elements_in_strict_mode() -> Result<()> {
+    let html = "<div>\n    <p>Text</p>\n</div>\n</div>";
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.children.len(), 1);
+    assert_eq!(dom.warnings.len(), 1);
+
+    let options = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let error = Dom::parse_with(html, &options).unwrap_err();
+    assert!(matches!(error, Error::Parsing(_)));
+    assert_eq!(
+        error.to_string(),
+        "Dangling end tag `</div>` at line 4, column 1"
+    );
+    assert!(Node::parse_with("<p>a</span></p>", &options).is_err());
+    assert!(Dom::parse_with("<div><p>Text</p></div>", &options).is_ok());
+    Ok(())
+}
+#[test]
 fn it_can_parse_broken_html() -> Result<()> {
     let html = "<div></span><div></div>";
     let dom = Dom::parse(html)?;
@@ -256,3 +328,385 @@ fn it_can_deal_with_weird_whitespaces() {
     let dom = Dom::parse(html).unwrap();
     assert_json_snapshot!(dom);
 }
+
+#[test]
+fn it_can_parse_custom_element_names() -> Result<()> {
+    for name in [
+        "my.widget",
+        "x_y",
+        "C4-t",
+        "math-α",
+        "emotion-😍",
+        "svg:rect",
+    ] {
+        let html = format!("<{name} id='a'>content</{name}>");
+        let dom = Dom::parse(&html)?;
+        let element = dom.children[0].element().unwrap();
+        assert_eq!(element.name, name);
+        assert_eq!(element.children.len(), 1);
+
+        // round trip through the formatted html
+        let output = dom.to_string();
+        assert_eq!(Dom::parse(&output)?.to_string(), output);
+    }
+    Ok(())
+}
+
+#[test]
+fn it_requires_element_names_to_start_with_a_letter() -> Result<()> {
+    let dom = Dom::parse("<.widget></.widget>")?;
+    assert!(dom.children.iter().all(|c| c.element().is_none()));
+    Ok(())
+}
+
+#[test]
+fn it_marks_raw_text_elements() -> Result<()> {
+    let dom = Dom::parse("<div><script>if (a < b) {}</script><style>p {}</style><textarea> <b> </textarea><p>text</p></div>")?;
+    let variants = dom.children[0]
+        .element()
+        .unwrap()
+        .children
+        .iter()
+        .map(|c| c.element().unwrap().variant.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        variants,
+        [
+            ElementVariant::RawText,
+            ElementVariant::RawText,
+            ElementVariant::RawText,
+            ElementVariant::Normal
+        ]
+    );
+
+    let json = Dom::parse("<script>let a = '<b>';</script>")?.children[0].to_json()?;
+    let script = Node::parse_json(&json)?;
+    assert_eq!(script.element().unwrap().variant, ElementVariant::RawText);
+    Ok(())
+}
+
+#[test]
+fn it_keeps_template_interpolations_in_classes() -> Result<()> {
+    let classes = |html| -> Result<Vec<String>> {
+        let dom = Dom::parse(html)?;
+        let element = dom.children[0].element().unwrap();
+        Ok(element.classes.iter().map(|c| c.to_string()).collect())
+    };
+    assert_eq!(classes("<p class='a {{x}} b'></p>")?, ["a", "{{x}}", "b"]);
+    assert_eq!(
+        classes("<p class='btn {{ active }} btn-{{ size }}'></p>")?,
+        ["btn", "{{ active }}", "btn-{{ size }}"]
+    );
+    assert_eq!(
+        classes("<p class='{% if a %}on{% endif %} {# note #}'></p>")?,
+        ["{% if a %}on{% endif %}", "{# note #}"]
+    );
+    assert_eq!(classes("<p class='a {{ b'></p>")?, ["a", "{{", "b"]);
+
+    let dom = Dom::parse(r#"<p class="a {{x}} b">text</p>"#)?;
+    assert_eq!(dom.to_string(), "<p class='a {{x}} b'>text</p>\n");
+    assert_eq!(Dom::parse(&dom.to_string())?.to_string(), dom.to_string());
+    Ok(())
+}
+
+#[test]
+fn it_keeps_script_content_as_single_text() -> Result<()> {
+    let scripts = [
+        "if (a < b && b > c) { run() }",
+        "const html = '<div class=\"x\"><p>not a tag</p></div>';",
+        "document.write(\"<b>bold</\" + \"b>\"); const end = '</div>';",
+        "// a < b <!-- not a comment --> a &amp; b",
+    ];
+    for script in scripts {
+        let html = format!("<script>{}</script>", script);
+        let dom = Dom::parse(&html)?;
+        let element = dom.children[0].element().unwrap();
+        assert_eq!(element.children, [Node::Text(script.into())]);
+        assert!(dom.to_string().contains(script));
+    }
+    Ok(())
+}
+
+#[test]
+fn it_keeps_style_content_as_single_text() -> Result<()> {
+    let css = "nav > ul > li:not(.active) > a[href^='/'] { color: red }";
+    let html = format!("<style>{}</style>", css);
+    let dom = Dom::parse(&html)?;
+    let element = dom.children[0].element().unwrap();
+    assert_eq!(element.children, [Node::Text(css.into())]);
+    assert_eq!(dom.to_string(), format!("{}\n", html));
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_custom_void_elements() -> Result<()> {
+    let mut options = ParseOptions::default();
+    options.void_elements.insert("x-icon".to_string());
+
+    let html = "<div><X-Icon name='close'><span>Close</span> text<x-icon/></div>";
+    let dom = Dom::parse_with(html, &options)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.children.len(), 4);
+
+    let icon = div.children[0].element().unwrap();
+    assert_eq!(icon.variant, ElementVariant::Void);
+    assert_eq!(icon.get_attribute("name"), Some("close"));
+    assert!(icon.children.is_empty());
+    assert_eq!(div.children[1].element().unwrap().name, "span");
+    assert_eq!(div.children[2], Node::Text(" text".into()));
+    assert!(div.children[3].element().unwrap().is_void());
+    Ok(())
+}
+
+#[test]
+fn it_keeps_unclosed_start_tags_as_text() -> Result<()> {
+    let html = "<div>a <x-icon name='a' name='b'> b<span>c</span></div>";
+    let dom = Dom::parse(html)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(
+        div.children[0],
+        Node::Text("a <x-icon name='a' name='b'> b".into())
+    );
+    assert_eq!(div.children[1].element().unwrap().name, "span");
+    assert!(dom.warnings.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_implies_the_end_of_list_items_and_paragraphs() -> Result<()> {
+    let dom = Dom::parse("<ul><li>a<li>b <b>bold</b>\n<li>c</ul><p>one<p>two<div>block</div>")?;
+    let ul = dom.children[0].element().unwrap();
+    let items = ul
+        .children
+        .iter()
+        .map(|c| {
+            let li = c.element().unwrap();
+            assert_eq!(li.name, "li");
+            assert_eq!(li.variant, ElementVariant::Normal);
+            li.text_content().trim().to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(items, ["a", "b bold", "c"]);
+
+    let names = dom
+        .children
+        .iter()
+        .map(|c| c.element().unwrap().name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["ul", "p", "p", "div"]);
+    assert!(dom.warnings.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_implies_the_end_of_table_and_definition_elements() -> Result<()> {
+    let html = "<table><tr><td>a<td>b<tr><th>c</table><dl><dt>t<dd>d</dl><select><option>x<option>y</select>";
+    let dom = Dom::parse(html)?;
+    let expected = indoc!(
+        "
+        <table>
+            <tr>
+                <td>a</td>
+                <td>b</td>
+            </tr>
+            <tr>
+                <th>c</th>
+            </tr>
+        </table>
+        <dl>
+            <dt>t</dt>
+            <dd>d</dd>
+        </dl>
+        <select>
+            <option>x</option>
+            <option>y</option>
+        </select>
+        "
+    );
+    assert_eq!(dom.to_string(), expected.replace("    ", "\t"));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_explicit_end_tags_of_optional_elements() -> Result<()> {
+    let dom = Dom::parse("<ul><li>a<ul><li>b</ul></li><li>c</li></ul><p>x</span>y</p>")?;
+    let ul = dom.children[0].element().unwrap();
+    assert_eq!(ul.children.len(), 2);
+    let nested = ul.children[0].element().unwrap().children[1]
+        .element()
+        .unwrap();
+    assert_eq!(nested.name, "ul");
+    assert_eq!(nested.children.len(), 1);
+
+    // a dangling end tag doesn't end the paragraph
+    let p = dom.children[1].element().unwrap();
+    assert_eq!(p.text_content(), "xy");
+    assert_eq!(dom.warnings.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_long_lists_without_end_tags() -> Result<()> {
+    let html = format!("<ul>{}</ul>", "<li>item".repeat(2000));
+    let dom = Dom::parse(&html)?;
+    assert_eq!(dom.children[0].element().unwrap().children.len(), 2000);
+    Ok(())
+}
+
+#[test]
+fn it_returns_an_error_for_deeply_nested_elements() {
+    let html = format!("{}{}", "<div>".repeat(10_000), "</div>".repeat(10_000));
+    assert!(Dom::parse(&html).is_err());
+}
+
+#[test]
+fn it_can_limit_the_depth_of_the_elements() -> Result<()> {
+    let options = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::default()
+    };
+    let dom = Dom::parse_with("<div><p>a</p></div><div>b</div>", &options)?;
+    assert_eq!(dom.children.len(), 2);
+
+    let error = Dom::parse_with("<div><p><span>a</span></p></div>", &options).unwrap_err();
+    assert!(matches!(error, Error::Parsing(_)));
+    assert_eq!(
+        error.to_string(),
+        "The maximum depth of 2 nested elements is exceeded at line 1, column 9"
+    );
+    assert!(Node::parse_with("<div><p><span>a</span></p></div>", &options).is_err());
+    Ok(())
+}
+
+#[test]
+fn it_limits_the_depth_by_default() {
+    // runs on a thread with the default stack size, so an overflow would abort the test
+    let result = std::thread::spawn(|| {
+        let html = format!("{}{}", "<div>".repeat(5_000), "</div>".repeat(5_000));
+        Dom::parse(&html).map(|_| ())
+    })
+    .join()
+    .unwrap();
+    assert!(result.is_err());
+
+    let html = format!("{}{}", "<div>".repeat(256), "</div>".repeat(256));
+    assert!(Dom::parse(&html).is_ok());
+}
+
+#[test]
+fn it_stores_the_depth_of_elements() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <ul>
+            <li><a href="/">Home</a></li>
+            <li>About</li>
+        </ul>
+        <p>End</p>
+    "#
+    );
+    let dom = Dom::parse(html)?;
+    let depths: Vec<_> = dom
+        .descendant_elements()
+        .map(|e| (e.name.as_ref(), e.depth))
+        .collect();
+    assert_eq!(
+        depths,
+        [("ul", 0), ("li", 1), ("a", 2), ("li", 1), ("p", 0)]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_sets_the_depth_of_parsed_inner_html() -> Result<()> {
+    let mut dom = Dom::parse("<div><section></section></div>")?;
+    let Node::Element(div) = &mut dom.children[0] else {
+        panic!("expected an element");
+    };
+    let Node::Element(section) = &mut div.children[0] else {
+        panic!("expected an element");
+    };
+    assert_eq!(section.depth, 1);
+    section.set_inner_html("<p><b>a</b></p>")?;
+    let depths: Vec<_> = section.children[0]
+        .descendant_elements()
+        .map(|e| e.depth)
+        .collect();
+    assert_eq!(depths, [2, 3]);
+    Ok(())
+}
+
+#[test]
+fn it_keeps_an_unclosed_div_as_normal_element() -> Result<()> {
+    let dom = Dom::parse("<div>Hello")?;
+    let Node::Element(div) = &dom.children[0] else {
+        panic!("expected an element");
+    };
+    assert_eq!(div.name, "div");
+    assert_eq!(div.variant, ElementVariant::Normal);
+    assert_eq!(div.children, [Node::Text("Hello".into())]);
+    assert_eq!(dom.children.len(), 1);
+    assert_eq!(dom.to_string(), "<div>Hello</div>\n");
+
+    // the content ends with the end of the parent
+    let dom = Dom::parse("<section><div>a <p>b</p>\n</section><i>c</i>")?;
+    assert_eq!(dom.children.len(), 2);
+    let section = dom.children[0].element().unwrap();
+    assert_eq!(section.children.len(), 1);
+    let div = section.children[0].element().unwrap();
+    assert_eq!(div.children[0], Node::Text("a ".into()));
+    let p = div.children[1].element().unwrap();
+    assert_eq!((p.name.as_ref(), p.depth), ("p", 2));
+    assert_eq!(div.source_span.text, "<div>a <p>b</p>");
+    assert_eq!(div.source_span.end_column, 25);
+
+    // unclosed elements are nested into each other up to the maximum depth
+    let dom = Dom::parse("<div>a<div>b")?;
+    let inner = dom.children[0].element().unwrap().children[1].element();
+    assert_eq!(inner.unwrap().children, [Node::Text("b".into())]);
+    assert!(Dom::parse(&"<div>".repeat(5_000)).is_err());
+    Ok(())
+}
+
+#[test]
+fn it_resolves_the_variant_of_html_elements_from_the_name() -> Result<()> {
+    let dom = Dom::parse("<div/><br/><x-icon/>")?;
+    let variants: Vec<_> = dom
+        .descendant_elements()
+        .map(|e| e.variant.clone())
+        .collect();
+    assert_eq!(
+        variants,
+        [
+            ElementVariant::Normal,
+            ElementVariant::Void,
+            ElementVariant::Void
+        ]
+    );
+    let dom = Dom::parse("<x-icon>Hello")?;
+    assert_eq!(dom.descendant_elements().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn it_matches_void_element_names_completely() -> Result<()> {
+    let dom = Dom::parse("<table><colgroup><col></colgroup></table>")?;
+    let names: Vec<_> = dom
+        .descendant_elements()
+        .map(|e| (e.name.as_ref(), e.variant.clone()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("table", ElementVariant::Normal),
+            ("colgroup", ElementVariant::Normal),
+            ("col", ElementVariant::Void)
+        ]
+    );
+
+    let dom = Dom::parse("<brick>a</brick>")?;
+    let brick = dom.children[0].element().unwrap();
+    assert_eq!(brick.name, "brick");
+    assert!(brick.attributes.is_empty());
+    assert_eq!(brick.children, [Node::Text("a".into())]);
+    Ok(())
+}
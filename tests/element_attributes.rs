@@ -1,5 +1,5 @@
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, ParseOptions, Result, Warning};
 
 #[test]
 fn it_can_parse_double_quote() -> Result<()> {
@@ -101,6 +101,34 @@ fn it_can_parse_classes() -> Result<()> {
     Ok(())
 }
 #[test]
+fn it_trims_classes_and_skips_empty_classes() -> Result<()> {
+    let dom = Dom::parse("<div class=\"  a   b \n\t\"></div><p class=' '></p>")?;
+    let classes: Vec<_> = dom
+        .descendant_elements()
+        .map(|e| e.classes.clone())
+        .collect();
+    assert_eq!(classes, [vec!["a", "b"], vec![]]);
+    assert_eq!(dom.to_string(), "<div class='a b'></div>\n<p></p>\n");
+    Ok(())
+}
+#[test]
+fn it_keeps_repeated_classes_once() -> Result<()> {
+    let dom = Dom::parse("<a class='btn Btn btn primary btn'></a>")?;
+    let a = dom.descendant_elements().next().unwrap();
+    assert_eq!(a.classes, ["btn", "Btn", "primary"]);
+
+    let dom = Dom::parse("<div Class='btn btn x' CLASS='y'></div>")?;
+    let div = dom.descendant_elements().next().unwrap();
+    assert_eq!(div.classes, ["btn", "x"]);
+    assert!(div.has_class("btn"));
+    assert!(div.attributes.is_empty());
+    assert!(matches!(
+        &dom.warnings[..],
+        [Warning::DuplicateAttribute { name, .. }] if name == "CLASS"
+    ));
+    Ok(())
+}
+#[test]
 fn it_keeps_spaces_for_non_classes() -> Result<()> {
     let html = "<img attr=' a b     \n\t'/>";
     let dom = Dom::parse(html)?;
@@ -114,3 +142,87 @@ fn it_can_parse_attribute_multiple_quotes() -> Result<()> {
     assert_json_snapshot!(dom);
     Ok(())
 }
+#[test]
+fn it_keeps_the_source_order_of_attributes() -> Result<()> {
+    let html = "<input type='text' name='user' id='name' autofocus data-b='2' data-a='1'>";
+    let dom = Dom::parse(html)?;
+    let input = dom.children[0].element().unwrap();
+    let keys = input
+        .attributes
+        .keys()
+        .map(|k| k.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        ["type", "name", "id", "autofocus", "data-b", "data-a"]
+    );
+
+    // the json output stays alphabetical for stable snapshots
+    assert!(dom
+        .to_json()?
+        .contains(r#"{"autofocus":null,"data-a":"1","data-b":"2","id":"name""#));
+    Ok(())
+}
+#[test]
+fn it_keeps_the_first_of_duplicate_attributes() -> Result<()> {
+    let html = "<a href='first' class='btn' HREF='second' class='other' href='third'>Link</a>";
+    let dom = Dom::parse(html)?;
+    let a = dom.children[0].element().unwrap();
+    assert_eq!(a.attributes.len(), 1);
+    assert_eq!(a.get_attribute("href"), Some("first"));
+    assert_eq!(a.classes, ["btn"]);
+
+    assert_eq!(
+        dom.warnings[0],
+        Warning::DuplicateAttribute {
+            name: "HREF".to_string(),
+            element: "a".to_string(),
+            line: 1,
+            column: 28,
+        }
+    );
+    let warnings = dom.warnings_as_strings();
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("Duplicate attribute `HREF` on element `a`"));
+    assert!(warnings[1].contains("Duplicate attribute `class` on element `a`"));
+    assert!(warnings[2].contains("Duplicate attribute `href` on element `a`"));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_case_of_names_by_default() -> Result<()> {
+    let dom = Dom::parse(r#"<DIV Class="x" Data-Id="1"><Cat/></DIV>"#)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.name, "DIV");
+    // the class attribute is always stored as the classes
+    assert_eq!(div.classes, ["x"]);
+    assert_eq!(div.attributes.keys().collect::<Vec<_>>(), ["Data-Id"]);
+    assert_eq!(div.children[0].element().unwrap().name, "Cat");
+    Ok(())
+}
+
+#[test]
+fn it_can_lowercase_names() -> Result<()> {
+    let options = ParseOptions {
+        lowercase_names: true,
+        ..ParseOptions::default()
+    };
+    let html = r#"<DIV Class="x" Data-Id="1" CLASS="y"><Cat/><BR><SCRIPT>A</SCRIPT></DIV>"#;
+    let dom = Dom::parse_with(html, &options)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.name, "div");
+    assert_eq!(div.classes, ["x"]);
+    assert_eq!(div.attributes.keys().collect::<Vec<_>>(), ["data-id"]);
+    assert_eq!(div.attributes.get("data-id"), Some(&Some("1".into())));
+    let names: Vec<_> = div
+        .children
+        .iter()
+        .map(|c| c.element().unwrap().name.as_ref())
+        .collect();
+    assert_eq!(names, ["cat", "br", "script"]);
+    assert!(matches!(
+        &dom.warnings[..],
+        [Warning::DuplicateAttribute { name, .. }] if name == "class"
+    ));
+    Ok(())
+}
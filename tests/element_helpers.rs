@@ -0,0 +1,374 @@
+use indoc::indoc;
+use lithtml::{Dom, Element, ElementVariant, Error, Node, Result};
+
+fn first_element<'a>(dom: &'a Dom<'a>) -> &'a Element<'a> {
+    dom.children[0].element().unwrap()
+}
+
+#[test]
+fn it_can_iterate_all_attributes() -> Result<()> {
+    let dom = Dom::parse(r#"<input type="text" class="a b  c" id="name" disabled>"#)?;
+    let attributes = first_element(&dom).all_attributes().collect::<Vec<_>>();
+    assert_eq!(
+        attributes,
+        vec![
+            ("class".to_string(), Some("a b c".to_string())),
+            ("disabled".to_string(), None),
+            ("id".to_string(), Some("name".to_string())),
+            ("type".to_string(), Some("text".to_string())),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_reconstructs_the_original_attributes() -> Result<()> {
+    let dom = Dom::parse(r#"<a href="/home" class="nav active" data-id='5'>Home</a>"#)?;
+    let attributes = first_element(&dom)
+        .all_attributes()
+        .map(|(k, v)| match v {
+            Some(v) => format!("{k}=\"{v}\""),
+            None => k,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (classes, reparsed) = lithtml::parse_attributes(&attributes)?;
+    assert_eq!(classes, first_element(&dom).classes);
+    assert_eq!(reparsed.len(), 2);
+    assert_eq!(reparsed["href"].as_deref(), Some("/home"));
+    assert_eq!(reparsed["data-id"].as_deref(), Some("5"));
+    Ok(())
+}
+
+#[test]
+fn it_has_no_class_attribute_without_classes() -> Result<()> {
+    let dom = Dom::parse("<div></div>")?;
+    assert_eq!(first_element(&dom).all_attributes().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn it_can_insert_before_and_after_a_matching_child() -> Result<()> {
+    let mut dom = Dom::parse("<ul><li>one</li><li class='last'>three</li></ul>")?;
+    let ul = match &mut dom.children[0] {
+        Node::Element(e) => e,
+        _ => unreachable!(),
+    };
+    let is_last = |n: &Node| matches!(n, Node::Element(e) if e.classes.contains(&"last".into()));
+
+    assert!(ul.insert_before(is_last, Node::new_text("two")));
+    assert!(ul.insert_after(is_last, Node::new_text("four")));
+    assert!(!ul.insert_before(|n| n.comment().is_some(), Node::new_text("none")));
+
+    let texts = ul
+        .children
+        .iter()
+        .map(|c| c.text().unwrap_or("li"))
+        .collect::<Vec<_>>();
+    assert_eq!(texts, vec!["li", "two", "li", "four"]);
+    Ok(())
+}
+
+#[test]
+fn it_can_match_any_tag() -> Result<()> {
+    let headings = ["h1", "h2", "h3", "h4", "h5", "h6"];
+    let dom = Dom::parse("<H2>Title</H2><p>Text</p>")?;
+    assert!(dom.children[0]
+        .element()
+        .unwrap()
+        .matches_any_tag(&headings));
+    assert!(!dom.children[1]
+        .element()
+        .unwrap()
+        .matches_any_tag(&headings));
+    assert!(!dom.children[1].element().unwrap().matches_any_tag(&[]));
+    Ok(())
+}
+
+#[test]
+fn it_can_distinguish_empty_and_void_elements() -> Result<()> {
+    let dom = Dom::parse("<span></span>")?;
+    assert!(first_element(&dom).is_empty_element());
+    assert!(!first_element(&dom).is_void());
+
+    let dom = Dom::parse("<br>")?;
+    assert!(!first_element(&dom).is_empty_element());
+    assert!(first_element(&dom).is_void());
+
+    let mut element = Element {
+        name: "p".into(),
+        variant: ElementVariant::Normal,
+        children: vec![Node::new_text(" \n\t ")],
+        ..Element::default()
+    };
+    assert!(element.is_empty_element());
+    element.children.push(Node::new_comment("note"));
+    assert!(!element.is_empty_element());
+
+    let dom = Dom::parse("<p> text </p>")?;
+    assert!(!first_element(&dom).is_empty_element());
+    assert!(!first_element(&dom).is_void());
+    Ok(())
+}
+
+#[test]
+fn it_can_get_attributes() -> Result<()> {
+    let dom = Dom::parse(r#"<input type="checkbox" Value="" disabled class="a">"#)?;
+    let input = first_element(&dom);
+
+    assert_eq!(input.get_attribute("type"), Some("checkbox"));
+    assert_eq!(input.get_attribute("TYPE"), Some("checkbox"));
+    assert_eq!(input.get_attribute("value"), Some(""));
+    assert_eq!(input.get_attribute("disabled"), None);
+    assert_eq!(input.get_attribute("name"), None);
+    assert_eq!(input.get_attribute("class"), None);
+
+    assert!(input.has_attribute("type"));
+    assert!(input.has_attribute("value"));
+    assert!(input.has_attribute("Disabled"));
+    assert!(!input.has_attribute("name"));
+    assert!(!input.has_attribute("class"));
+    Ok(())
+}
+
+#[test]
+fn it_can_check_classes_and_the_id() -> Result<()> {
+    let dom = Dom::parse(r#"<div id="main" class="card  card-large active"></div>"#)?;
+    let div = first_element(&dom);
+    assert!(div.has_class("card"));
+    assert!(div.has_class(" active "));
+    assert!(div.has_class("card-large"));
+    assert!(!div.has_class("large"));
+    assert!(!div.has_class("card card-large"));
+    assert_eq!(div.id(), Some("main"));
+
+    let dom = Dom::parse("<p>no id</p>")?;
+    let p = first_element(&dom);
+    assert!(!p.has_class("p"));
+    assert_eq!(p.id(), None);
+    Ok(())
+}
+
+#[test]
+fn it_can_add_and_remove_classes() -> Result<()> {
+    let dom = Dom::parse(r#"<a class="nav active">Home</a>"#)?;
+    let mut a = first_element(&dom).clone();
+    a.add_class("active");
+    a.add_class(" new ".to_string());
+    a.add_class("new");
+    a.add_class("  ");
+    assert_eq!(a.classes, ["nav", "active", "new"]);
+
+    assert!(a.remove_class("nav"));
+    assert!(!a.remove_class("missing"));
+    assert_eq!(a.classes, ["active", "new"]);
+    Ok(())
+}
+
+#[test]
+fn it_can_set_and_remove_attributes() -> Result<()> {
+    let dom = Dom::parse(r#"<a href="/old" target="_blank" disabled>Home</a>"#)?;
+    let mut a = first_element(&dom).clone();
+    a.set_attribute("HREF", "/new".to_string());
+    a.set_attribute("rel", "noopener");
+    a.set_attribute("disabled", "true");
+    assert_eq!(a.get_attribute("href"), Some("/new"));
+    assert_eq!(a.get_attribute("disabled"), Some("true"));
+    let keys = a.attributes.keys().map(|k| k.as_ref()).collect::<Vec<_>>();
+    assert_eq!(keys, ["href", "target", "disabled", "rel"]);
+
+    assert!(a.remove_attribute("Target"));
+    assert!(!a.remove_attribute("missing"));
+    assert!(!a.has_attribute("target"));
+    assert_eq!(
+        a.to_string(),
+        "<a disabled='true' href='/new' rel='noopener'>Home</a>"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_insert_and_remove_children() -> Result<()> {
+    let dom = Dom::parse("<ul><li>one</li><li>three</li></ul>")?;
+    let mut ul = first_element(&dom).clone();
+    ul.insert_child(1, Node::parse("<li>two</li>")?.remove(0))?;
+    ul.insert_child(3, Node::parse("<li>four</li>")?.remove(0))?;
+    assert!(ul.insert_child(5, Node::new_text("five")).is_err());
+    assert_eq!(
+        ul.to_string(),
+        "<ul>\n\t<li>one</li>\n\t<li>two</li>\n\t<li>three</li>\n\t<li>four</li>\n</ul>"
+    );
+
+    let removed = ul.remove_child(0).unwrap();
+    assert_eq!(removed.element().unwrap().text_content(), "one");
+    assert!(ul.remove_child(3).is_none());
+    assert_eq!(ul.children.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn it_rejects_children_of_void_elements() -> Result<()> {
+    let dom = Dom::parse("<img src='a.png'>")?;
+    let mut img = first_element(&dom).clone();
+    assert_eq!(img.variant, ElementVariant::Void);
+    assert!(matches!(
+        img.insert_child(0, Node::new_text("text")),
+        Err(Error::Manipulation(_))
+    ));
+    assert!(img.children.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_normalize_adjacent_texts() -> Result<()> {
+    let mut dom = Dom::parse("<div><p>a</p></div>")?;
+    if let Node::Element(div) = &mut dom.children[0] {
+        div.children.push(Node::new_text("b"));
+        div.children.push(Node::new_text(""));
+        div.children.push(Node::new_text("c"));
+        if let Node::Element(p) = &mut div.children[0] {
+            p.children.insert(0, Node::new_text(""));
+            p.children.push(Node::new_text("d"));
+        }
+    }
+    dom.children.push(Node::new_text("e"));
+    dom.children.push(Node::new_text("f"));
+    dom.normalize();
+
+    assert_eq!(dom.children.len(), 2);
+    assert_eq!(dom.children[1], Node::new_text("ef"));
+    let div = first_element(&dom);
+    assert_eq!(div.children.len(), 2);
+    assert_eq!(div.children[1], Node::new_text("bc"));
+    assert_eq!(
+        div.children[0].element().unwrap().children,
+        [Node::new_text("ad")]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_normalize_an_element() {
+    let mut element = Element {
+        name: "p".into(),
+        variant: ElementVariant::Normal,
+        children: vec![Node::new_text("Hello "), Node::new_text("world")],
+        ..Element::default()
+    };
+    element.normalize();
+    assert_eq!(element.children, [Node::new_text("Hello world")]);
+}
+
+#[test]
+fn it_can_parse_the_style_attribute() -> Result<()> {
+    let dom = Dom::parse(r#"<p style="color: red; margin : 0 auto">a</p>"#)?;
+    let style = first_element(&dom).style();
+    assert_eq!(
+        style.into_iter().collect::<Vec<_>>(),
+        [("color", "red"), ("margin", "0 auto")]
+    );
+
+    let dom = Dom::parse(r#"<p style=" color:red ;; ;width: ;">a</p>"#)?;
+    let style = first_element(&dom).style();
+    assert_eq!(style.into_iter().collect::<Vec<_>>(), [("color", "red")]);
+
+    let dom = Dom::parse(r#"<p style="color: red; color: blue">a</p>"#)?;
+    assert_eq!(first_element(&dom).style()["color"], "blue");
+
+    let dom = Dom::parse(r#"<p style='background: url("a;b.png"); content: ";"'>a</p>"#)?;
+    let style = first_element(&dom).style();
+    assert_eq!(style["background"], r#"url("a;b.png")"#);
+    assert_eq!(style["content"], r#"";""#);
+
+    let dom = Dom::parse("<p>a</p>")?;
+    assert!(first_element(&dom).style().is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_get_the_data_attributes() -> Result<()> {
+    let dom =
+        Dom::parse(r#"<div id="a" data-id="5" data-flag Data-User-Name="b" data="c"></div>"#)?;
+    let div = first_element(&dom);
+    assert_eq!(
+        div.dataset().into_iter().collect::<Vec<_>>(),
+        [("User-Name", Some("b")), ("flag", None), ("id", Some("5"))]
+    );
+    assert_eq!(div.data_attr("id"), Some("5"));
+    assert_eq!(div.data_attr("user-name"), Some("b"));
+    assert_eq!(div.data_attr("flag"), None);
+    assert_eq!(div.data_attr("missing"), None);
+    assert!(Element::default().dataset().is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_compare_the_tag_name_case_insensitive() -> Result<()> {
+    let dom = Dom::parse("<DIV></DIV><div></div><Div></Div><span></span>")?;
+    let matches: Vec<_> = dom
+        .children
+        .iter()
+        .map(|n| n.element().unwrap().tag_eq_ignore_ascii_case("div"))
+        .collect();
+    assert_eq!(matches, [true, true, true, false]);
+    Ok(())
+}
+
+#[test]
+fn it_can_get_the_inner_and_outer_html() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <div id="main">
+            <p class="a">Hello <b>world</b></p>
+            <img src="x.png">
+            <pre>  keep
+          this</pre>
+        </div>"#
+    );
+    let dom = Dom::parse(html)?;
+    let div = first_element(&dom);
+    let inner = concat!(
+        r#"<p class='a'>Hello <b>world</b></p><img src='x.png'>"#,
+        "<pre>  keep\n  this</pre>"
+    );
+    assert_eq!(div.inner_html(), inner);
+    assert_eq!(div.outer_html(), format!("<div id='main'>{inner}</div>"));
+
+    let p = div.children[0].element().unwrap();
+    assert_eq!(p.inner_html(), "Hello <b>world</b>");
+    assert_eq!(p.outer_html(), "<p class='a'>Hello <b>world</b></p>");
+    let pre = div.children[4].element().unwrap();
+    assert_eq!(pre.inner_html(), "  keep\n  this");
+    Ok(())
+}
+
+#[test]
+fn it_can_set_the_inner_html() -> Result<()> {
+    let mut dom = Dom::parse("<div id='main'><p>old</p></div>")?;
+    if let Node::Element(div) = &mut dom.children[0] {
+        let html = String::from("<h1>Title</h1>Some <b>text</b>");
+        div.set_inner_html(&html)?;
+        drop(html);
+        assert_eq!(div.children.len(), 3);
+        assert_eq!(div.inner_html(), "<h1>Title</h1>Some <b>text</b>");
+    }
+    assert_eq!(
+        dom.children[0].element().unwrap().outer_html(),
+        "<div id='main'><h1>Title</h1>Some <b>text</b></div>"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_rejects_inner_html_of_void_elements() -> Result<()> {
+    let mut dom = Dom::parse("<div><br></div>")?;
+    if let Node::Element(div) = &mut dom.children[0] {
+        assert!(div.set_inner_html("<!-- open").is_err());
+        assert_eq!(div.children.len(), 1);
+        if let Node::Element(br) = &mut div.children[0] {
+            let error = br.set_inner_html("text").unwrap_err();
+            assert!(matches!(error, Error::Manipulation(_)));
+        }
+    }
+    Ok(())
+}
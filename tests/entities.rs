@@ -0,0 +1,69 @@
+use lithtml::{Dom, Node, ParseOptions, Result};
+use std::borrow::Cow;
+
+#[test]
+fn it_can_keep_entities() -> Result<()> {
+    let options = ParseOptions {
+        decode_entities: false,
+        ..ParseOptions::default()
+    };
+    let dom = Dom::parse_with("<p title='&lt;'>a &amp; b</p>", &options)?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0].text(), Some("a &amp; b"));
+    assert_eq!(p.get_attribute("title"), Some("&lt;"));
+    Ok(())
+}
+
+#[test]
+fn it_can_decode_entities_in_text_and_attributes() -> Result<()> {
+    let html = "<p title='&quot;Tom&quot; &amp; Jerry' class='a&#45;b c'>&lt;b&gt; &copy; &#169; &#xA9;&#x1F600;&euro;</p>Caf&eacute;";
+    let dom = Dom::parse(html)?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0].text(), Some("<b> © © ©😀€"));
+    assert_eq!(p.get_attribute("title"), Some("\"Tom\" & Jerry"));
+    assert_eq!(p.classes, ["a-b", "c"]);
+    assert_eq!(dom.children[1].text(), Some("Café"));
+    Ok(())
+}
+
+#[test]
+fn it_only_allocates_for_decoded_text() -> Result<()> {
+    let nodes = Node::parse("<p>plain</p><p>a &amp; b</p>")?;
+    let text = |i: usize| nodes[i].element().unwrap().children[0].clone();
+    assert!(matches!(text(0), Node::Text(Cow::Borrowed("plain"))));
+    assert!(matches!(text(1), Node::Text(Cow::Owned(t)) if t == "a & b"));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_malformed_entities() -> Result<()> {
+    let html = "<p>&notanentity; & bare &amp &#; &#xZZ; &#0; &#1114112; &lt</p>";
+    let dom = Dom::parse(html)?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(
+        p.children[0].text(),
+        Some("&notanentity; & bare &amp &#; &#xZZ; &#0; &#1114112; &lt")
+    );
+    Ok(())
+}
+
+#[test]
+fn it_decodes_only_escapable_raw_text() -> Result<()> {
+    let html = "<title>A &amp; B</title><textarea>&lt;p&gt;</textarea><script>a &amp;&amp; b</script><style>p::after { content: '&gt;' }</style>";
+    let dom = Dom::parse(html)?;
+    let texts = dom
+        .children
+        .iter()
+        .map(|c| c.element().unwrap().children[0].text().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "A & B",
+            "<p>",
+            "a &amp;&amp; b",
+            "p::after { content: '&gt;' }"
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use lithtml::{Dom, DomBuilder, DomVariant, Error, Event, Result};
+
+/// Write the events in a short form to compare them
+fn outline(dom: &Dom) -> Vec<String> {
+    dom.events()
+        .map(|event| match event {
+            Event::StartElement {
+                name,
+                classes,
+                attributes,
+            } => {
+                let attributes: Vec<_> = attributes.keys().map(|k| k.as_ref()).collect();
+                format!("<{name} {classes:?} {attributes:?}>")
+            }
+            Event::EndElement { name } => format!("</{name}>"),
+            Event::Text(text) => format!("'{text}'"),
+            Event::Comment(comment) => format!("<!--{comment}-->"),
+            other => format!("{other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn it_emits_the_events_in_document_order() -> Result<()> {
+    let dom = Dom::parse("<a><b>x</b></a>")?;
+    let events: Vec<_> = dom.events().collect();
+    assert!(matches!(
+        events.as_slice(),
+        [
+            Event::StartElement { name: "a", .. },
+            Event::StartElement { name: "b", .. },
+            Event::Text("x"),
+            Event::EndElement { name: "b" },
+            Event::EndElement { name: "a" },
+        ]
+    ));
+    Ok(())
+}
+
+#[test]
+fn it_emits_end_events_for_void_elements_and_siblings() -> Result<()> {
+    let dom = Dom::parse("<!--c--><p class='x' id='p'>a<br>b</p><![CDATA[d]]>")?;
+    assert_eq!(
+        outline(&dom),
+        [
+            "<!--c-->",
+            r#"<p ["x"] ["id"]>"#,
+            "'a'",
+            r#"<br [] []>"#,
+            "</br>",
+            "'b'",
+            "</p>",
+            r#"CData("d")"#,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_build_a_dom_from_events() -> Result<()> {
+    let mut builder = DomBuilder::new();
+    builder
+        .push_comment("nav")?
+        .push_start("ul", &[("class", Some("menu  main")), ("id", Some("nav"))])?
+        .push_start("li", &[])?
+        .push_start("a", &[("href", Some("/")), ("hidden", None)])?
+        .push_text("Home")?
+        .push_end()?
+        .push_start("br", &[])?
+        .push_end()?
+        .push_end()?
+        .push_end()?;
+    let dom = builder.finish()?;
+
+    let html =
+        r#"<!--nav--><ul class="menu main" id="nav"><li><a href="/" hidden>Home</a><br></li></ul>"#;
+    assert_eq!(dom.to_string(), Dom::parse(html)?.to_string());
+    assert_eq!(dom.tree_type, DomVariant::DocumentFragment);
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unmatched_events_in_the_builder() {
+    let mut builder = DomBuilder::new();
+    assert!(matches!(builder.push_end(), Err(Error::Manipulation(_))));
+
+    builder.push_start("img", &[]).unwrap();
+    assert!(matches!(
+        builder.push_text("x"),
+        Err(Error::Manipulation(_))
+    ));
+
+    builder.push_end().unwrap();
+    builder.push_start("p", &[]).unwrap();
+    assert!(matches!(builder.finish(), Err(Error::Manipulation(_))));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "fuzz")]
+
+use lithtml::{Dom, Node};
+
+/// Html fragments which are likely to hit edge cases of the grammar
+const TOKENS: &[&str] = &[
+    "<",
+    ">",
+    "</",
+    "/>",
+    "<!",
+    "<?",
+    "?>",
+    "<!--",
+    "-->",
+    "<!--[if IE]>",
+    "<![endif]-->",
+    "<!doctype html>",
+    "html",
+    "head",
+    "body",
+    "div",
+    "p",
+    "li",
+    "br",
+    "script",
+    "style",
+    "title",
+    "svg",
+    "path",
+    "=",
+    "\"",
+    "'",
+    " ",
+    "\t",
+    "\n",
+    "\r\n",
+    "a",
+    "b",
+    "x-y",
+    ":",
+    ".",
+    "&amp;",
+    "&#169;",
+    "&",
+    "<![CDATA[",
+    "]]>",
+    "é",
+    "😍",
+    "\u{0}",
+    "\u{feff}",
+];
+
+/// Small xorshift pseudo random generator, to keep the fuzz runs reproducible
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+}
+
+fn random_input(random: &mut Random) -> String {
+    let len = random.below(64);
+    if random.below(4) == 0 {
+        // plain random bytes
+        let bytes = (0..len).map(|_| random.next() as u8).collect::<Vec<_>>();
+        String::from_utf8_lossy(&bytes).to_string()
+    } else {
+        (0..len)
+            .map(|_| TOKENS[random.below(TOKENS.len())])
+            .collect()
+    }
+}
+
+#[test]
+fn it_never_panics_on_random_input() {
+    let iterations = std::env::var("FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(100_000);
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..iterations {
+        let input = random_input(&mut random);
+        // Ok or Err are both fine, only a panic fails the test
+        let _ = Dom::parse(&input);
+        let _ = Node::parse(&input);
+    }
+}
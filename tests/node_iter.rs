@@ -1,5 +1,5 @@
 use indoc::indoc;
-use lithtml::{Dom, Node, Result};
+use lithtml::{Dir, Dom, Error, Node, Result};
 
 #[test]
 fn it_can_iter_1() -> Result<()> {
@@ -17,8 +17,8 @@ fn it_can_iter_1() -> Result<()> {
             </body>
         </html>
     "};
-    let dom = Dom::parse(&html)?;
-    let root = dom.children.get(0).unwrap().into_iter();
+    let dom = Dom::parse(html)?;
+    let root = dom.children.first().unwrap().into_iter();
     let num_li = root.into_iter().fold(0, |mut acc, curr| match curr {
         Node::Element(ref e) => {
             if e.name == "li" {
@@ -31,3 +31,169 @@ fn it_can_iter_1() -> Result<()> {
     assert_eq!(num_li, 3);
     Ok(())
 }
+
+#[test]
+fn it_can_iter_over_the_whole_dom() -> Result<()> {
+    let html = "<div><p>a</p><!--b--></div>c<ul><li>d<i>e</i></li></ul>";
+    let dom = Dom::parse(html)?;
+    let visited: Vec<String> = dom
+        .into_iter()
+        .map(|node| match node {
+            Node::Element(e) => e.name.to_string(),
+            Node::Text(t) => t.to_string(),
+            Node::Comment(c) => format!("<!--{c}-->"),
+            Node::CData(_) | Node::ProcessingInstruction(_) | Node::ConditionalComment { .. } => {
+                String::new()
+            }
+        })
+        .collect();
+    assert_eq!(
+        visited,
+        ["div", "p", "a", "<!--b-->", "c", "ul", "li", "d", "i", "e"]
+    );
+
+    let mut count = 0;
+    for _ in &dom {
+        count += 1;
+    }
+    assert_eq!(count, 10);
+    Ok(())
+}
+
+#[test]
+fn it_can_iter_over_the_elements() -> Result<()> {
+    let html = indoc! {"
+        <div>
+            text
+            <!-- comment -->
+            <p>a <b>b</b></p>
+        </div>
+        <span>c</span>
+    "};
+    let dom = Dom::parse(html)?;
+    let names: Vec<_> = dom.descendant_elements().map(|e| e.name.as_ref()).collect();
+    assert_eq!(names, ["div", "p", "b", "span"]);
+
+    let div = &dom.children[0];
+    assert_eq!(div.descendant_elements().count(), 3);
+    assert_eq!(dom.children[1].descendant_elements().count(), 1);
+    assert_eq!(Node::new_text("text").descendant_elements().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn it_can_navigate_by_paths() -> Result<()> {
+    let html = "<ul><li>a</li><li><b>b</b> c</li></ul><p>d</p>";
+    let dom = Dom::parse(html)?;
+    let (path, b) = dom
+        .into_iter()
+        .with_paths()
+        .find(|(_, node)| node.element().is_some_and(|e| e.name == "b"))
+        .unwrap();
+    assert_eq!(path, [0, 1, 0]);
+    assert_eq!(dom.node_at(&path), Some(b));
+
+    let li = dom.parent_of(&path).unwrap();
+    assert_eq!(li.text_content(), "b c");
+    assert_eq!(dom.parent_of(&[0, 1]).unwrap().name, "ul");
+    assert_eq!(dom.parent_of(&[0]), None);
+
+    assert_eq!(dom.next_sibling(&path), Some(&Node::new_text(" c")));
+    assert_eq!(dom.next_sibling(&[0, 1]), None);
+    assert_eq!(dom.next_sibling(&[0]).unwrap().element().unwrap().name, "p");
+    assert_eq!(dom.node_at(&[2]), None);
+    Ok(())
+}
+
+#[test]
+fn it_can_yield_the_paths_of_all_nodes() -> Result<()> {
+    let dom = Dom::parse("<div><p>a</p></div>b")?;
+    let paths: Vec<_> = dom.into_iter().with_paths().map(|(path, _)| path).collect();
+    assert_eq!(paths, [vec![0], vec![0, 0], vec![0, 0, 0], vec![1]]);
+    for path in paths {
+        assert!(dom.node_at(&path).is_some());
+    }
+    Ok(())
+}
+
+#[test]
+fn it_can_replace_nodes_by_paths() -> Result<()> {
+    let mut dom = Dom::parse("<p><b>a</b> and <b>b</b></p>")?;
+    let paths: Vec<_> = dom
+        .into_iter()
+        .with_paths()
+        .filter(|(_, node)| node.element().is_some_and(|e| e.name == "b"))
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(paths, [vec![0, 0], vec![0, 2]]);
+
+    // replace from the last path, so the earlier paths stay valid
+    for path in paths.iter().rev() {
+        let mut strong = dom
+            .node_at(path)
+            .unwrap()
+            .element()
+            .unwrap()
+            .clone()
+            .into_owned();
+        strong.name = "strong".into();
+        dom.replace_with(path, vec![strong.into()])?;
+    }
+    assert_eq!(
+        dom.to_string(),
+        Dom::parse("<p><strong>a</strong> and <strong>b</strong></p>")?.to_string()
+    );
+
+    // a node can be replaced by several nodes or removed
+    dom.replace_with(
+        &[0, 1],
+        vec![Node::new_text(" or "), Node::new_comment("x")],
+    )?;
+    dom.replace_with(&[0, 0], vec![])?;
+    assert_eq!(dom.children[0].element().unwrap().children.len(), 3);
+
+    assert!(matches!(
+        dom.replace_with(&[0, 5], vec![]),
+        Err(Error::Manipulation(_))
+    ));
+    assert!(matches!(
+        dom.replace_with(&[], vec![]),
+        Err(Error::Manipulation(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn it_can_query_the_inherited_dir_lang_and_translate() -> Result<()> {
+    let html = indoc! {r#"
+        <div lang="ar" dir="RTL" translate="no">
+            <p dir="invalid"><b>a</b></p>
+            <p dir="ltr" lang="en" translate><i>b</i></p>
+            <p lang="">c</p>
+        </div>
+        <span>d</span>
+    "#};
+    let dom = Dom::parse(html)?;
+    let path = |name: &str| {
+        dom.into_iter()
+            .with_paths()
+            .find(|(_, node)| node.element().is_some_and(|e| e.name == name))
+            .map(|(path, _)| path)
+            .unwrap()
+    };
+
+    let (b, i, span) = (path("b"), path("i"), path("span"));
+    assert_eq!(dom.effective_dir(&b), Dir::Rtl);
+    assert_eq!(dom.effective_dir(&i), Dir::Ltr);
+    assert_eq!(dom.effective_dir(&span), Dir::Ltr);
+
+    assert_eq!(dom.effective_lang(&b), Some("ar"));
+    assert_eq!(dom.effective_lang(&i), Some("en"));
+    assert_eq!(dom.effective_lang(&[0, 4, 0]), Some(""));
+    assert_eq!(dom.effective_lang(&span), None);
+
+    assert!(!dom.effective_translate(&b));
+    assert!(dom.effective_translate(&i));
+    assert!(dom.effective_translate(&span));
+    Ok(())
+}
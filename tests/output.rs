@@ -1,6 +1,9 @@
 use indoc::indoc;
 use insta::{assert_json_snapshot, assert_snapshot};
-use lithtml::{Dom, Result};
+use lithtml::{
+    AttributeOrder, Dom, Element, ElementVariant, FormattingOptions, IndentStyle, LineEnding, Node,
+    ParseOptions, Result, Syntax,
+};
 
 #[test]
 fn it_can_output_json() -> Result<()> {
@@ -88,3 +91,543 @@ fn it_handles_class_output_correct() -> Result<()> {
     assert_snapshot!(new_html);
     Ok(())
 }
+
+#[test]
+fn it_can_output_truncated_html() -> Result<()> {
+    let dom = Dom::parse("<div><p>Hällo wörld</p><p>second</p></div>")?;
+    let o = FormattingOptions::pretty();
+    let full = dom.to_string();
+    assert_eq!(full, dom.to_html_truncated(&o, full.len()));
+
+    let truncated = dom.to_html_truncated(&o, 13);
+    assert_eq!(truncated, format!("{}...", &full[..13]));
+    assert_eq!(truncated, "<div>\n\t<p>Hä...");
+
+    // never cut inside a multi byte character
+    let truncated = dom.to_html_truncated(&o, 12);
+    assert_eq!(truncated, "<div>\n\t<p>H...");
+    Ok(())
+}
+
+#[test]
+fn it_keeps_whitespace_between_inline_elements() -> Result<()> {
+    let html = "<p><span>a</span> <span>b</span><b>c</b></p>";
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.to_string(), format!("{html}\n"));
+    assert_eq!(Dom::parse(&dom.to_string())?.to_string(), dom.to_string());
+    Ok(())
+}
+
+#[test]
+fn it_can_configure_inline_elements() -> Result<()> {
+    let dom = Dom::parse("<p><x-tag>a</x-tag><x-tag>b</x-tag></p>")?;
+    assert_eq!(
+        dom.to_string(),
+        "<p>\n\t<x-tag>a</x-tag>\n\t<x-tag>b</x-tag>\n</p>\n"
+    );
+
+    let mut o = FormattingOptions::pretty();
+    o.inline_elements.insert("x-tag".to_string());
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &o).unwrap();
+    assert_eq!(html, "<p><x-tag>a</x-tag><x-tag>b</x-tag></p>\n");
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_whitespace_of_configured_inline_elements() -> Result<()> {
+    let dom = Dom::parse("<p><x-a>a</x-a> <x-b>b</x-b></p>")?;
+    let mut o = FormattingOptions::pretty();
+    o.inline_elements = ["x-a", "x-b"].iter().map(|e| e.to_string()).collect();
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &o).unwrap();
+    assert_eq!(html, "<p><x-a>a</x-a> <x-b>b</x-b></p>\n");
+
+    // without inline elements the whitespace is replaced by the formatting
+    let dom = Dom::parse("<p><span>a</span> <span>b</span></p>")?;
+    o.inline_elements.clear();
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &o).unwrap();
+    assert_eq!(html, "<p>\n\t<span>a</span>\n\t<span>b</span>\n</p>\n");
+
+    // whitespace next to block elements isn't written in a single line either
+    let dom = Dom::parse("<div><p>a</p>\n <p>b</p> <b>c</b> <i>d</i></div>")?;
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &FormattingOptions::compact())
+        .unwrap();
+    assert_eq!(html, "<div><p>a</p><p>b</p><b>c</b> <i>d</i></div>");
+    Ok(())
+}
+
+#[test]
+fn it_can_order_attributes() -> Result<()> {
+    let dom = Dom::parse(r#"<a title="t" class="c" href="/" id="i"></a>"#)?;
+    let output = |attribute_order| {
+        let o = FormattingOptions {
+            attribute_order,
+            ..FormattingOptions::pretty()
+        };
+        let mut html = String::new();
+        dom.fmt_opt(&mut html, &o).unwrap();
+        html
+    };
+
+    assert_eq!(
+        output(AttributeOrder::Alphabetical),
+        "<a class='c' href='/' id='i' title='t'></a>\n"
+    );
+    assert_eq!(
+        output(AttributeOrder::SourceOrder),
+        "<a class='c' title='t' href='/' id='i'></a>\n"
+    );
+    assert_eq!(
+        output(AttributeOrder::Priority(vec!["id".into(), "class".into()])),
+        "<a id='i' class='c' href='/' title='t'></a>\n"
+    );
+    assert_eq!(
+        output(AttributeOrder::Priority(vec!["title".into()])),
+        "<a title='t' class='c' href='/' id='i'></a>\n"
+    );
+    assert_eq!(
+        output(AttributeOrder::Custom(|a, b| b.cmp(a))),
+        "<a class='c' title='t' id='i' href='/'></a>\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_indent_with_tabs_or_spaces() -> Result<()> {
+    let dom = Dom::parse("<div><ul><li>one</li></ul></div>")?;
+    let format = |indent| {
+        let o = FormattingOptions {
+            indent,
+            ..FormattingOptions::pretty()
+        };
+        let mut html = String::new();
+        dom.fmt_opt(&mut html, &o).unwrap();
+        html
+    };
+
+    assert_eq!(
+        format(IndentStyle::Tab),
+        "<div>\n\t<ul>\n\t\t<li>one</li>\n\t</ul>\n</div>\n"
+    );
+    assert_eq!(format(IndentStyle::Tab), dom.to_string());
+    assert_eq!(
+        format(IndentStyle::Spaces(2)),
+        "<div>\n  <ul>\n    <li>one</li>\n  </ul>\n</div>\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_output_crlf_line_endings() -> Result<()> {
+    let html = indoc!(
+        r#"<div class="container" id="main" data-description="a long attribute value">
+            <!-- comment -->
+            <p>Text</p>
+            <ul><li>one</li><li>two</li></ul>
+        </div>"#
+    );
+    let dom = Dom::parse(html)?;
+    let o = FormattingOptions {
+        line_ending: LineEnding::Crlf,
+        ..FormattingOptions::pretty()
+    };
+    let mut crlf = String::new();
+    dom.fmt_opt(&mut crlf, &o).unwrap();
+
+    assert_eq!(
+        crlf.matches("\r\n").count(),
+        dom.to_string().matches('\n').count()
+    );
+    assert_eq!(crlf.matches('\n').count(), crlf.matches("\r\n").count());
+    assert_eq!(crlf.replace("\r\n", "\n"), dom.to_string());
+    assert_eq!(Dom::parse(&crlf)?.to_string(), dom.to_string());
+    Ok(())
+}
+
+#[test]
+fn it_encodes_text_and_attribute_values() -> Result<()> {
+    let element = Element {
+        name: "p".into(),
+        variant: ElementVariant::Normal,
+        attributes: [("title".into(), Some(r#"<"it's" & more>"#.into()))]
+            .into_iter()
+            .collect(),
+        children: vec![Node::new_text("a < b && c > d")],
+        ..Element::default()
+    };
+    assert_eq!(
+        element.to_string(),
+        "<p title='&lt;\"it&#39;s\" &amp; more>'>a &lt; b &amp;&amp; c &gt; d</p>"
+    );
+
+    let o = FormattingOptions {
+        double_quot: true,
+        ..FormattingOptions::pretty()
+    };
+    let mut html = String::new();
+    Node::Element(element).fmt_opt(&mut html, &o, 0).unwrap();
+    assert!(html.starts_with(r#"<p title="&lt;&quot;it's&quot; &amp; more>">"#));
+    Ok(())
+}
+
+#[test]
+fn it_can_round_trip_text_with_special_characters() -> Result<()> {
+    let dom = Dom::parse("<div><p>a < b && c</p><b>x&gt;y</b></div>")?;
+    let html = dom.to_string();
+    let again = Dom::parse(&html)?;
+    assert_eq!(again.to_string(), html);
+
+    let div = again.children[0].element().unwrap();
+    assert_eq!(
+        div.children[0].element().unwrap().children[0].text(),
+        Some("a < b && c")
+    );
+    // the new line between the block and the inline element is kept as text
+    assert_eq!(
+        div.children[2].element().unwrap().children[0].text(),
+        Some("x>y")
+    );
+
+    // every `&` is encoded, so the decoded text doesn't change
+    let html = "<p title='&amp;lt;'>&amp;copy; AT&amp;T</p>";
+    let dom = Dom::parse(html)?;
+    let output = dom.to_string();
+    let again = Dom::parse(&output)?;
+    let p = again.children[0].element().unwrap();
+    assert_eq!(p.get_attribute("title"), Some("&lt;"));
+    assert_eq!(p.children[0].text(), Some("&copy; AT&T"));
+    assert_eq!(again, dom);
+    Ok(())
+}
+
+#[test]
+fn it_round_trips_references_with_the_default_options() -> Result<()> {
+    let html = "<p><a href='?x=1&amp;y=2'>Tom &amp; Jerry</a> <x-icon> &lt;?php ?&gt;</p>";
+    let dom = Dom::parse(html)?;
+    let output = dom.to_string();
+    assert_eq!(
+        output,
+        "<p><a href='?x=1&amp;y=2'>Tom &amp; Jerry</a> &lt;x-icon&gt; &lt;?php ?&gt;</p>\n"
+    );
+    assert_eq!(Dom::parse(&output)?, dom);
+    assert_eq!(Dom::parse(&output)?.to_string(), output);
+
+    let dom = Dom::parse("<p>Tom &amp; Jerry</p>")?;
+    let output = dom.to_string();
+    let again = Dom::parse(&output)?;
+    assert_eq!(again.to_string(), "<p>Tom &amp; Jerry</p>\n");
+    assert_eq!(again, dom);
+    Ok(())
+}
+
+#[test]
+fn it_encodes_every_ampersand() {
+    let node = Node::Text("write &lt; for <".into());
+    assert_eq!(node.to_string(), "write &amp;lt; for &lt;");
+}
+
+#[test]
+fn it_does_not_encode_scripts_and_styles() -> Result<()> {
+    let html = "<script>if (a < b && c > d) {}</script><style>a > b {}</style><title>a & b</title>";
+    let dom = Dom::parse(html)?;
+    assert_eq!(
+        dom.to_string(),
+        "<script>if (a < b && c > d) {}</script>\n<style>a > b {}</style>\n<title>a &amp; b</title>\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_write_html_into_writers() -> Result<()> {
+    let dom = Dom::parse("<div id='a'><p>Text &amp; more</p><img src='a.png'></div>")?;
+    let o = FormattingOptions::pretty();
+
+    let mut string = String::new();
+    dom.write_html(&mut string, &o).unwrap();
+    let mut bytes = Vec::new();
+    dom.write_html_io(&mut bytes, &o)?;
+
+    assert_eq!(string, dom.to_string());
+    assert_eq!(String::from_utf8(bytes).unwrap(), string);
+    Ok(())
+}
+
+#[test]
+fn it_returns_the_io_error_of_the_writer() -> Result<()> {
+    let dom = Dom::parse("<div><p>Text</p></div>")?;
+    let mut buffer = [0u8; 8];
+    let result = dom.write_html_io(&mut buffer[..], &FormattingOptions::pretty());
+    assert!(matches!(result, Err(lithtml::Error::IO(_))));
+    assert!(buffer.starts_with(b"<div>\n"));
+    Ok(())
+}
+
+#[test]
+fn it_can_minify_the_output() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <!-- page -->
+        <div class="container"   id="main">
+            <p>
+                Some   text with
+                <b>bold</b>   words
+            </p>
+            <div></div>
+            <img src="a.png">
+            <pre>  keep
+              this  </pre>
+            <script>if (a < b) {}</script>
+        </div>
+    "#
+    );
+    let dom = Dom::parse(html)?;
+    let mut minified = String::new();
+    dom.fmt_opt(&mut minified, &FormattingOptions::compact())
+        .unwrap();
+
+    assert_eq!(
+        minified,
+        "<!-- page --><div class='container' id='main'><p>Some text with <b>bold</b> words</p>\
+         <div></div><img src='a.png'><pre>  keep\n      this  </pre>\
+         <script>if (a < b) {}</script></div>"
+    );
+    assert!(Dom::parse(&minified)?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_insertion_order_without_sorting() -> Result<()> {
+    let mut element = Element {
+        name: "a".into(),
+        variant: ElementVariant::Normal,
+        ..Element::default()
+    };
+    element.set_attribute("title", "t");
+    element.set_attribute("href", "/");
+    element.set_attribute("data-z", "z");
+    element.set_attribute("data-a", "a");
+    element.children.push(Node::new_text("Link"));
+
+    let o = FormattingOptions {
+        attribute_order: AttributeOrder::SourceOrder,
+        ..FormattingOptions::pretty()
+    };
+    let mut html = String::new();
+    element.fmt_opt(&mut html, &o, 0).unwrap();
+    assert_eq!(html, "<a title='t' href='/' data-z='z' data-a='a'>Link</a>");
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_whitespace_of_preformatted_content() -> Result<()> {
+    let html = indoc!(
+        "
+        <div>
+            <pre>
+          fn main() {
+              println!(\"a  b\");
+          }
+        <b>  bold  </b>
+        </pre>
+            <textarea>  line 1
+          line 2  </textarea>
+        </div>"
+    );
+    let pre = "<pre>\n  fn main() {\n      println!(\"a  b\");\n  }\n<b>  bold  </b>\n</pre>";
+    let dom = Dom::parse(html)?;
+    let output = dom.to_string();
+    assert!(output.contains(pre), "{output}");
+    assert!(
+        output.contains("<textarea>  line 1\n  line 2  </textarea>"),
+        "{output}"
+    );
+    assert!(Dom::parse(&output)?.semantically_eq(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_whitespace_of_raw_text_content() -> Result<()> {
+    let textarea = "<textarea>\n  line1\n    line2\n</textarea>";
+    let script = "<script>\n    if (a) {\n        b();\n    }\n</script>";
+    let style = "<style>\n  a { color: red; }\n</style>";
+    for html in [textarea, script, style] {
+        let dom = Dom::parse(html)?;
+        let output = dom.to_string();
+        assert_eq!(output, format!("{html}\n"));
+        assert_eq!(Dom::parse(&output)?, dom);
+    }
+    Ok(())
+}
+
+#[test]
+fn it_can_wrap_long_text() -> Result<()> {
+    let html = indoc!(
+        "
+        <div>
+            <p>Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.</p>
+            <pre>Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.</pre>
+        </div>
+    "
+    );
+    let dom = Dom::parse(html)?;
+    let o = FormattingOptions {
+        max_len: 40,
+        wrap_text: true,
+        ..FormattingOptions::pretty()
+    };
+    let mut output = String::new();
+    dom.fmt_opt(&mut output, &o).unwrap();
+
+    assert_eq!(
+        output,
+        indoc!(
+            "
+            <div>
+            \t<p>
+            \t\tLorem ipsum dolor sit amet,
+            \t\tconsectetur adipiscing elit, sed
+            \t\tdo eiusmod tempor incididunt ut
+            \t\tlabore et dolore magna aliqua.
+            \t</p>
+            \t<pre>Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.</pre>
+            </div>
+            "
+        )
+    );
+    assert!(Dom::parse(&output)?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_can_wrap_long_text_with_inline_elements() -> Result<()> {
+    let html = "<div><p>The quick brown fox jumps over the lazy dog, which is sleeping far away <b>bold</b> after <a href='/a b'>a long link text</a>, <i>x</i>.</p><p>Short <b>text</b></p></div>";
+    let dom = Dom::parse(html)?;
+    let o = FormattingOptions {
+        max_len: 30,
+        wrap_text: true,
+        ..FormattingOptions::pretty()
+    };
+    let mut output = String::new();
+    dom.fmt_opt(&mut output, &o).unwrap();
+
+    assert_eq!(
+        output,
+        indoc!(
+            "
+            <div>
+            \t<p>
+            \t\tThe quick brown fox
+            \t\tjumps over the lazy
+            \t\tdog, which is sleeping
+            \t\tfar away <b>bold</b>
+            \t\tafter <a href='/a b'>a
+            \t\tlong link text</a>,
+            \t\t<i>x</i>.
+            \t</p>
+            \t<p>Short <b>text</b></p>
+            </div>
+            "
+        )
+    );
+    assert!(Dom::parse(&output)?.equals_ignoring_whitespace(&dom));
+    Ok(())
+}
+
+#[test]
+fn it_selects_the_quote_of_attribute_values() -> Result<()> {
+    let mut element = Element {
+        name: "p".into(),
+        variant: ElementVariant::Normal,
+        ..Element::default()
+    };
+    element.set_attribute("a", "say 'hi'");
+    element.set_attribute("b", r#"say "hi""#);
+    element.set_attribute("c", r#"she said "hi" and 'bye'"#);
+
+    for (double_quot, c) in [
+        (false, r#"c='she said "hi" and &#39;bye&#39;'"#),
+        (true, r#"c="she said &quot;hi&quot; and 'bye'""#),
+    ] {
+        let o = FormattingOptions {
+            double_quot,
+            ..FormattingOptions::compact()
+        };
+        let mut html = String::new();
+        element.fmt_opt(&mut html, &o, 0).unwrap();
+        assert!(html.contains(r#"a="say 'hi'""#));
+        assert!(html.contains(r#"b='say "hi"'"#));
+        assert!(html.contains(c));
+
+        let options = ParseOptions {
+            decode_entities: true,
+            ..ParseOptions::default()
+        };
+        let dom = Dom::parse_with(&html, &options)?;
+        assert_eq!(dom.children[0], Node::Element(element.clone()));
+    }
+    Ok(())
+}
+
+#[test]
+fn it_can_expand_boolean_attributes() -> Result<()> {
+    let dom = Dom::parse("<input type='checkbox' checked disabled>")?;
+    let output = |expand_boolean_attributes| {
+        let o = FormattingOptions {
+            double_quot: true,
+            expand_boolean_attributes,
+            ..FormattingOptions::compact()
+        };
+        let mut html = String::new();
+        dom.fmt_opt(&mut html, &o).unwrap();
+        html
+    };
+
+    assert_eq!(output(false), r#"<input checked disabled type="checkbox">"#);
+    assert_eq!(
+        output(true),
+        r#"<input checked="checked" disabled="disabled" type="checkbox">"#
+    );
+    Ok(())
+}
+
+#[test]
+fn it_writes_empty_and_void_elements_by_syntax() -> Result<()> {
+    let dom = Dom::parse("<p><span></span><br></p>")?;
+    for (syntax, compact, pretty) in [
+        (
+            Syntax::Html5,
+            "<p><span></span><br></p>",
+            "<p>\n\t<span></span>\n\t<br>\n</p>\n",
+        ),
+        (
+            Syntax::Xhtml,
+            "<p><span></span><br /></p>",
+            "<p>\n\t<span></span>\n\t<br />\n</p>\n",
+        ),
+    ] {
+        for (o, expected) in [
+            (FormattingOptions::compact(), compact),
+            (FormattingOptions::pretty(), pretty),
+        ] {
+            let o = FormattingOptions { syntax, ..o };
+            let mut html = String::new();
+            dom.fmt_opt(&mut html, &o).unwrap();
+            assert_eq!(html, expected);
+            assert!(Dom::parse(&html)?.semantically_eq(&dom));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn it_keeps_other_void_elements_self_closing_in_html5() -> Result<()> {
+    let dom = Dom::parse("<svg><path d='M0 0'/></svg>")?;
+    let mut html = String::new();
+    dom.fmt_opt(&mut html, &FormattingOptions::compact())
+        .unwrap();
+    assert_eq!(html, "<svg><path d='M0 0'/></svg>");
+    Ok(())
+}
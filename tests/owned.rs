@@ -0,0 +1,124 @@
+use indoc::indoc;
+use lithtml::{Dom, Element, Node, Result};
+use std::borrow::Cow;
+
+const HTML: &str = indoc!(
+    r#"<div id="main" class="a b">
+        <!-- comment -->
+        <p>Hello <b>world</b></p>
+        <img src="x.png" alt>
+    </div>"#
+);
+
+#[test]
+fn it_can_deep_clone_into_an_owned_dom() -> Result<()> {
+    let owned: Dom<'static> = {
+        let html = HTML.to_string();
+        let dom = Dom::parse(&html)?;
+        let owned = dom.deep_clone_owned();
+        assert_eq!(owned, dom);
+        owned
+    };
+
+    // the source string is dropped, but the dom is still usable
+    assert_eq!(owned.to_string(), Dom::parse(HTML)?.to_string());
+    assert_eq!(owned.to_json()?, Dom::parse(HTML)?.to_json()?);
+    let div = owned.children[0].element().unwrap();
+    assert_eq!(div.source_span.start_line, 1);
+    assert!(div.source_span.text.starts_with("<div"));
+    Ok(())
+}
+
+#[test]
+fn it_can_convert_into_an_owned_dom() -> Result<()> {
+    let expected = Dom::parse(HTML)?;
+    let owned: Dom<'static> = {
+        let html = HTML.to_string();
+        Dom::parse(&html)?.into_owned()
+    };
+
+    // the source string is dropped, but the dom is still usable
+    assert_eq!(owned, expected);
+    assert_eq!(owned.to_string(), expected.to_string());
+    assert_eq!(owned.to_json()?, expected.to_json()?);
+    let div = owned.children[0].element().unwrap();
+    assert!(div.source_span.text.starts_with("<div"));
+
+    let node: Node<'static> = {
+        let html = "<p class='a'>Hello</p>".to_string();
+        Node::parse(&html)?.remove(0).into_owned()
+    };
+    assert_eq!(node.to_string(), "<p class='a'>Hello</p>");
+    Ok(())
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn it_is_send_and_sync_when_owned() {
+    assert_send_sync::<Dom<'static>>();
+    assert_send_sync::<Node<'static>>();
+    assert_send_sync::<Element<'static>>();
+}
+
+#[test]
+fn it_can_process_owned_doms_on_multiple_threads() -> Result<()> {
+    let doms = (0..4)
+        .map(|i| Ok(Dom::parse(&format!("<p>{i}</p>"))?.deep_clone_owned()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let texts = std::thread::scope(|scope| {
+        let handles = doms
+            .iter()
+            .map(|dom| scope.spawn(move || dom.children[0].element().unwrap().rendered_text()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(texts, vec!["0", "1", "2", "3"]);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_owned_nodes() -> Result<()> {
+    let nodes: Vec<Node<'static>> = Node::parse_owned(&format!("<p>{}</p> text", "dynamic"))?;
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes, Node::parse("<p>dynamic</p> text")?);
+    assert_eq!(nodes[1].text(), Some(" text"));
+    assert!(Node::parse_owned("<!-- open").is_err());
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_a_dom_from_str() -> Result<()> {
+    let owned: Dom<'static> = HTML.to_string().parse()?;
+    assert_eq!(owned, Dom::parse(HTML)?);
+
+    let dom = Dom::try_from(HTML)?;
+    assert_eq!(dom, Dom::parse(HTML)?);
+    Ok(())
+}
+
+#[test]
+fn it_returns_an_error_when_parsing_from_str_fails() {
+    assert!("<!-- open".parse::<Dom>().is_err());
+    assert!(Dom::try_from("<!DOCTYPE html><html></html><html></html>").is_err());
+}
+
+#[test]
+fn it_does_not_allocate_known_element_names() -> Result<()> {
+    let html = String::from("<div><x-icon></x-icon></div>");
+    let dom = Dom::parse(&html)?.into_owned();
+    let names: Vec<_> = dom.descendant_elements().map(|e| &e.name).collect();
+    assert!(matches!(names[0], Cow::Borrowed("div")));
+    assert!(matches!(names[1], Cow::Owned(name) if name == "x-icon"));
+
+    let json = Dom::parse(&html)?.to_json()?;
+    let dom = Dom::parse_json(&json)?;
+    assert!(dom
+        .descendant_elements()
+        .all(|e| matches!(e.name, Cow::Borrowed(_))));
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use indoc::indoc;
+use lithtml::{Dom, ParseOptions, Result};
+
+const HTML: &str = indoc!(
+    r#"<header><h1 class="title">Title</h1></header>
+    <main>
+        <p>First <b>bold</b> paragraph</p>
+        <p id="second">Second paragraph</p>
+        <ul><li>one</li><li>two</li></ul>
+    </main>
+    <footer>Footer</footer>"#
+);
+
+/// Apply the edit to the html and check the re-parsed dom against a full parse
+fn assert_reparse(find: &str, replace: &str) -> Result<()> {
+    let start = HTML.find(find).expect("edit not found");
+    let edit = start..start + find.len();
+    let new_input = format!("{}{}{}", &HTML[..edit.start], replace, &HTML[edit.end..]);
+
+    let mut dom = Dom::parse(HTML)?;
+    dom.reparse_range(HTML, &new_input, edit)?;
+    assert_same_tree(&dom, &Dom::parse(&new_input)?);
+    Ok(())
+}
+
+/// Compare the doms including the source spans and depths, which are ignored by `PartialEq`
+fn assert_same_tree(dom: &Dom, parsed: &Dom) {
+    assert_eq!(dom, parsed);
+    let spans = |dom: &Dom| {
+        dom.descendant_elements()
+            .map(|e| format!("{} {:?}", e.depth, e.source_span))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(spans(dom), spans(parsed));
+}
+
+#[test]
+fn it_can_reparse_an_edit_within_an_element() -> Result<()> {
+    assert_reparse("bold", "very bold")?;
+    assert_reparse("Second", "2nd")?;
+    assert_reparse("two", "two</li><li>three")?;
+    assert_reparse(r#"id="second""#, r#"class="new""#)?;
+    assert_reparse("", "")?;
+    Ok(())
+}
+
+#[test]
+fn it_can_reparse_an_edit_changing_lines() -> Result<()> {
+    assert_reparse("First", "First\n\n")?;
+    assert_reparse("paragraph</p>\n    <p", "paragraph</p><p")?;
+    assert_reparse("Title", "Ünicode\r\nTitle")?;
+    Ok(())
+}
+
+#[test]
+fn it_can_reparse_an_edit_crossing_elements() -> Result<()> {
+    assert_reparse("</header>\n<main>", "</header><div>")?;
+    assert_reparse("<p id", "<section id")?;
+    assert_reparse("<footer>", "")?;
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_reparse_an_invalid_edit() -> Result<()> {
+    let mut dom = Dom::parse(HTML)?;
+    assert!(dom.reparse_range(HTML, HTML, 5..HTML.len() + 1).is_err());
+    assert!(dom.reparse_range(HTML, "<p>different</p>", 0..1).is_err());
+    assert_eq!(dom, Dom::parse(HTML)?);
+    Ok(())
+}
+
+/// Apply the edit to the input and check the re-parsed dom against a full parse with the options
+fn assert_reparse_with(
+    input: &str,
+    find: &str,
+    replace: &str,
+    options: &ParseOptions,
+) -> Result<()> {
+    let start = input.find(find).expect("edit not found");
+    let edit = start..start + find.len();
+    let new_input = format!("{}{}{}", &input[..edit.start], replace, &input[edit.end..]);
+
+    let mut dom = Dom::parse_with(input, options)?;
+    dom.reparse_range_with(input, &new_input, edit, options)?;
+    assert_same_tree(&dom, &Dom::parse_with(&new_input, options)?);
+    Ok(())
+}
+
+#[test]
+fn it_can_reparse_an_edit_within_preformatted_content() -> Result<()> {
+    let options = ParseOptions::default();
+    let html = "<pre><span>  <b>a</b> x</span></pre>";
+    assert_reparse_with(html, "x", "y", &options)?;
+    let html = "<div><pre>\n  <p>  <b>a</b> x</p>\n</pre></div>";
+    assert_reparse_with(html, "x", "y", &options)?;
+    Ok(())
+}
+
+#[test]
+fn it_reparses_with_the_parse_options() -> Result<()> {
+    let options = ParseOptions {
+        lowercase_names: true,
+        decode_entities: true,
+        ..ParseOptions::default()
+    };
+    let html = "<DIV><P Class='A'>Tom &amp; Jerry</P><P>x</P></DIV>";
+    assert_reparse_with(html, "Jerry", "Spike &copy;", &options)?;
+    assert_reparse_with(html, "x", "<B>y &lt; z</B>", &options)?;
+    Ok(())
+}
+
+#[test]
+fn it_reparses_implied_end_tags_like_a_full_parse() -> Result<()> {
+    let options = ParseOptions::default();
+    let html = "<div>\n  <p>hello <b>world</b></p>\n  <ul><li>a</li></ul>\n</div>";
+    assert_reparse_with(html, "</p>", "</p&amp;>", &options)?;
+    assert_reparse_with("<div><p>one<p>two</div>", "one", "<td>one", &options)?;
+    assert_reparse_with("<select><option>a<option>b</select>", "a", "<p>a", &options)?;
+    assert_reparse_with("<div><ul><li>a<li>b</ul></div>", "b", "c", &options)?;
+    Ok(())
+}
+
+/// Small xorshift pseudo random generator, to keep the edits reproducible
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max as u64) as usize
+    }
+}
+
+#[test]
+fn it_reparses_random_edits_like_a_full_parse() -> Result<()> {
+    const INPUTS: &[&str] = &[
+        HTML,
+        "<div>\n  <p>hello <b>world</b></p>\n  <ul><li>one<li>two</ul>\n</div>",
+        "<section><div><p>one<p>two</div><table><tr><td>a<td>b</table></section>",
+        "<form><select><option>a<option>b</select><dl><dt>x<dd>y</dl></form>",
+        "<div><pre> <i>a</i> </pre><svg><a href='#'/><g>b</g></svg><br><span>c</span></div>",
+    ];
+    const TOKENS: &[&str] = &[
+        "", "x", " ", "\n", "<", ">", "</", "/>", "<p>", "</p>", "<li>", "<td>", "<div>", "</div>",
+        "<b>", "</b>", "<br>", "&amp;", "<!--", "-->", "'", "=",
+    ];
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..2_000 {
+        let input = INPUTS[random.below(INPUTS.len())];
+        let start = random.below(input.len() + 1);
+        let end = (start + random.below(6)).min(input.len());
+        let replace = TOKENS[random.below(TOKENS.len())];
+        let new_input = format!("{}{}{}", &input[..start], replace, &input[end..]);
+
+        let parsed = match Dom::parse(&new_input) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        let mut dom = Dom::parse(input)?;
+        dom.reparse_range(input, &new_input, start..end)?;
+        assert_same_tree(&dom, &parsed);
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use indoc::indoc;
+use lithtml::{Dom, Node, Result};
+
+#[test]
+fn it_can_sanitize_urls() -> Result<()> {
+    let html = indoc!(
+        r#"<a href="javascript:alert(1)">js</a>
+        <a href="https://example.com" title="javascript:ok">https</a>
+        <a href="/relative/path?q=a:b">relative</a>
+        <a href="page.html#top">fragment</a>
+        <img src="data:image/png;base64,AAAA">
+        <form action=" JavaScript:alert(1)"><button formaction="vbscript:msgbox(1)">go</button></form>
+        <a href="mailto:someone@example.com">mail</a>"#
+    );
+    let mut dom = Dom::parse(html)?;
+    dom.sanitize_urls(&["http", "https", "mailto"]);
+
+    let urls = dom.attributes_matching(|key, _| key != "title");
+    let urls = urls
+        .iter()
+        .map(|(e, key, value)| (e.name.as_ref(), *key, value.unwrap_or_default()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            ("a", "href", "https://example.com"),
+            ("a", "href", "/relative/path?q=a:b"),
+            ("a", "href", "page.html#top"),
+            ("a", "href", "mailto:someone@example.com"),
+        ]
+    );
+    assert_eq!(dom.attributes_matching(|key, _| key == "title").len(), 1);
+    Ok(())
+}
+
+#[test]
+fn it_can_allow_data_urls() -> Result<()> {
+    let mut dom = Dom::parse(r#"<img src="DATA:image/png;base64,AAAA"><img src="javascript:x">"#)?;
+    dom.sanitize_urls(&["data"]);
+    assert_eq!(dom.attributes_matching(|key, _| key == "src").len(), 1);
+    Ok(())
+}
+
+#[test]
+fn it_sanitizes_obfuscated_schemes() -> Result<()> {
+    for url in [
+        "java\tscript:alert(1)",
+        "\n javascript:alert(1)",
+        "javascript&colon;alert(1)",
+        "javascript&#58;alert(1)",
+        "javascript&#x3A;alert(1)",
+        "jav&#x09;ascript:alert(1)",
+        "&#106;avascript:alert(1)",
+        "JAVASCRIPT:alert(1)",
+    ] {
+        let html =
+            format!(r#"<svg><a xlink:href="{url}"><text>x</text></a></svg><a href="{url}">x</a>"#);
+        let mut dom = Dom::parse(&html)?;
+        dom.sanitize_urls(&["https"]);
+        assert!(dom.attributes_matching(|_, _| true).is_empty(), "{url}");
+    }
+    Ok(())
+}
+
+#[test]
+fn it_can_remove_all_scripts() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <html>
+            <head><script src="a.js"></script><title>Title</title></head>
+            <body>
+                <script>alert(1)</script>
+                <div><p>Text<script>alert(2)</script></p></div>
+            </body>
+        </html>"#
+    );
+    let is_script = |n: &Node| n.element().is_some_and(|e| e.name == "script");
+    let mut dom = Dom::parse(html)?;
+    assert_eq!(dom.into_iter().filter(|n| is_script(n)).count(), 3);
+
+    dom.retain(|n| !is_script(n));
+    assert_eq!(dom.into_iter().filter(|n| is_script(n)).count(), 0);
+    assert_eq!(dom.select("title")?.len(), 1);
+    assert_eq!(dom.select("div > p")?[0].inner_html(), "Text");
+
+    if let Node::Element(html) = &mut dom.children[0] {
+        html.retain_children(|n| n.element().is_none_or(|e| e.name != "head"));
+    }
+    assert!(dom.select("head")?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use indoc::indoc;
+use lithtml::{Dom, Element, Error, Result};
+
+const HTML: &str = indoc!(
+    r#"<div id="page">
+        <nav class="menu main">
+            <a class="btn" href="/home">Home</a>
+            <a class="btn active" href="/about" data-id="2">About</a>
+            <a class="btn">No link</a>
+        </nav>
+        <article>
+            <h1>Title</h1>
+            <p>First <a href="/inline">link</a></p>
+            <div><p>Nested</p></div>
+        </article>
+        <P>Upper</P>
+    </div>"#
+);
+
+fn texts(elements: Vec<&Element>) -> Vec<String> {
+    elements.iter().map(|e| e.rendered_text()).collect()
+}
+
+#[test]
+fn it_can_select_by_tag_id_and_class() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    assert_eq!(texts(dom.select("h1")?), ["Title"]);
+    assert_eq!(texts(dom.select("p")?), ["First link", "Nested", "Upper"]);
+    assert_eq!(dom.select("#page")?.len(), 1);
+    assert_eq!(texts(dom.select(".active")?), ["About"]);
+    assert_eq!(dom.select(".menu.main")?.len(), 1);
+    assert_eq!(dom.select("*")?.len(), 12);
+    Ok(())
+}
+
+#[test]
+fn it_can_select_with_compound_selectors() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    assert_eq!(texts(dom.select("a.btn[href]")?), ["Home", "About"]);
+    assert_eq!(texts(dom.select("a[href='/about']")?), ["About"]);
+    assert_eq!(texts(dom.select("a[data-id=2].active")?), ["About"]);
+    assert_eq!(texts(dom.select("h1, .active")?), ["About", "Title"]);
+    Ok(())
+}
+
+#[test]
+fn it_can_select_with_combinators() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    assert_eq!(texts(dom.select("article p")?), ["First link", "Nested"]);
+    assert_eq!(texts(dom.select("article > p")?), ["First link"]);
+    assert_eq!(texts(dom.select("#page > p")?), ["Upper"]);
+    assert_eq!(texts(dom.select("#page article a")?), ["link"]);
+    assert_eq!(texts(dom.select("div div p")?), ["Nested"]);
+    assert_eq!(texts(dom.select("nav>a.active")?), ["About"]);
+    assert!(dom.select("article > div > h1")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_can_select_within_an_element() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    let article = dom.select("article")?[0];
+    assert_eq!(texts(article.select("p")?), ["First link", "Nested"]);
+    assert_eq!(texts(article.select("article > p")?), ["First link"]);
+    assert!(article.select("article")?.is_empty());
+    assert!(article.select("#page p")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_select_with_an_invalid_selector() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    for selector in ["", "div >", "a[href", ".", "#", "a,", "p ~ p"] {
+        assert!(dom.select(selector).is_err(), "{selector}");
+    }
+    assert!(dom.select("section")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_select_with_pseudo_classes() -> Result<()> {
+    let dom = Dom::parse(HTML)?;
+    for selector in [
+        "p:first-child",
+        "a:hover",
+        ".x:hover",
+        "li:nth-child(2n)",
+        "p::before",
+    ] {
+        let error = dom.select(selector).unwrap_err();
+        assert!(matches!(error, Error::Parsing(_)), "{selector}");
+    }
+    assert_eq!(
+        dom.select("nav a:hover").unwrap_err().to_string(),
+        "The pseudo-class :hover of the selector isn't supported"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_select_namespaced_and_escaped_names() -> Result<()> {
+    let html = r##"<svg:svg><svg:rect xlink:href="#a"/></svg:svg><p class="md:flex">a</p>"##;
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.select("svg|rect")?.len(), 1);
+    assert_eq!(dom.select(r"svg\:svg > svg\:rect")?.len(), 1);
+    assert_eq!(dom.select("[xlink|href='#a']")?.len(), 1);
+    assert_eq!(texts(dom.select(r".md\:flex")?), ["a"]);
+    assert!(dom.select("rect")?.is_empty());
+    Ok(())
+}
@@ -14,3 +14,27 @@ fn it_can_generate_source_span() -> Result<()> {
     assert_debug_snapshot!(dom);
     Ok(())
 }
+
+#[test]
+fn it_can_output_the_source_spans_as_json() -> Result<()> {
+    let html = "<div>\n    <p class=\"a\">text</p>\n    <!-- comment -->\n</div>";
+    let dom = Dom::parse(html)?;
+    let output = dom.to_json_with_spans()?;
+    let json: serde_json::Value = serde_json::from_str(&output)?;
+    let div = &json["children"][0];
+    assert_eq!(
+        div["sourceSpan"],
+        serde_json::json!({"startLine": 1, "endLine": 4, "startColumn": 1, "endColumn": 7})
+    );
+    assert_eq!(div["children"][0]["name"], "p");
+    assert_eq!(
+        div["children"][0]["sourceSpan"],
+        serde_json::json!({"startLine": 2, "endLine": 2, "startColumn": 5, "endColumn": 26})
+    );
+    assert_eq!(div["children"][1], " comment ");
+    assert!(!dom.to_json()?.contains("sourceSpan"));
+
+    let json = dom.to_json()?;
+    assert_eq!(Dom::parse_json(&output)?, Dom::parse_json(&json)?);
+    Ok(())
+}
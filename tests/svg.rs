@@ -1,6 +1,6 @@
 use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, Node, Result};
 
 #[test]
 fn it_can_parse_svg() -> Result<()> {
@@ -41,5 +41,121 @@ fn it_can_parse_complex_svg() {
         </svg>
     "#
     );
-    assert!(Dom::parse(&svg).is_ok());
+    assert!(Dom::parse(svg).is_ok());
+}
+
+#[test]
+fn it_can_parse_svg_elements_with_children() -> Result<()> {
+    let dom = Dom::parse("<svg><path d='M0 0'><title>Tooltip</title></path><rect x='1'></svg>")?;
+    let svg = dom.children[0].element().unwrap();
+    assert!(svg.is_foreign());
+    assert_eq!(svg.children.len(), 2);
+
+    let path = svg.children[0].element().unwrap();
+    assert!(!path.is_void());
+    assert_eq!(path.children[0].element().unwrap().name, "title");
+    assert!(svg.children[1].element().unwrap().is_void());
+    Ok(())
+}
+
+#[test]
+fn it_can_round_trip_mathml() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <p>
+            <math display="block" xmlns="http://www.w3.org/1998/Math/MathML">
+                <mrow>
+                    <msup><mi>x</mi><mn>2</mn></msup>
+                    <mo>+</mo>
+                    <mspace width="1em"/>
+                    <mo form="prefix" stretchy="false">(</mo>
+                    <mi mathvariant="bold-italic">y</mi>
+                    <mo>)</mo>
+                </mrow>
+            </math>
+        </p>
+    "#
+    );
+    let dom = Dom::parse(html)?;
+    assert!(dom.warnings.is_empty());
+
+    let math = &dom.children[0].element().unwrap().children[0];
+    assert!(math.element().unwrap().is_foreign());
+    let names = math
+        .into_iter()
+        .filter_map(Node::element)
+        .map(|e| e.name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["mrow", "msup", "mi", "mn", "mo", "mspace", "mo", "mi", "mo"]
+    );
+
+    let output = dom.to_string();
+    assert!(output.contains("<mspace width='1em'/>"));
+    assert!(output.contains("<mo form='prefix' stretchy='false'>(</mo>"));
+    let reparsed = Dom::parse(&output)?;
+    assert!(reparsed.equals_ignoring_whitespace(&dom));
+    assert_eq!(reparsed.to_string(), output);
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_xml_declaration_before_the_svg_root() -> Result<()> {
+    let html = indoc!(
+        r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <svg xmlns="http://www.w3.org/2000/svg">
+            <circle cx="5" cy="5" r="4"/>
+        </svg>
+    "#
+    );
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.tree_type, lithtml::DomVariant::DocumentFragment);
+    assert_eq!(
+        dom.children[0],
+        Node::ProcessingInstruction(r#"<?xml version="1.0" encoding="UTF-8"?>"#.into())
+    );
+    assert_eq!(dom.children[1].element().unwrap().name, "svg");
+
+    let output = dom.to_string();
+    assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg"));
+    assert_eq!(Dom::parse(&output)?, dom);
+    Ok(())
+}
+
+#[test]
+fn it_can_parse_namespaced_names() -> Result<()> {
+    let dom = Dom::parse(r##"<svg:rect xlink:href="#a"/>"##)?;
+    let rect = dom.children[0].element().unwrap();
+    assert_eq!(rect.name, "svg:rect");
+    assert_eq!(rect.namespace(), Some("svg"));
+    assert_eq!(rect.local_name(), "rect");
+    assert_eq!(rect.get_attribute("xlink:href"), Some("#a"));
+    assert_eq!(dom.to_string(), "<svg:rect xlink:href='#a'/>\n");
+    Ok(())
+}
+
+#[test]
+fn it_has_no_namespace_without_colon() -> Result<()> {
+    let dom = Dom::parse("<svg><rect/></svg>")?;
+    let svg = dom.children[0].element().unwrap();
+    assert_eq!(svg.namespace(), None);
+    assert_eq!(svg.local_name(), "svg");
+    Ok(())
+}
+
+#[test]
+fn it_keeps_self_closing_elements_of_foreign_content_void() -> Result<()> {
+    let dom = Dom::parse("<svg><a href='#'/><g><title/></g></svg><div/>")?;
+    let svg = dom.children[0].element().unwrap();
+    assert!(svg.children[0].element().unwrap().is_void());
+    let g = svg.children[1].element().unwrap();
+    assert!(g.children[0].element().unwrap().is_void());
+    assert!(!dom.children[1].element().unwrap().is_void());
+    assert_eq!(
+        dom.to_string(),
+        "<svg>\n\t<a href='#'/>\n\t<g>\n\t\t<title/>\n\t</g>\n</svg>\n<div></div>\n"
+    );
+    Ok(())
 }
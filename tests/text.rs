@@ -1,6 +1,6 @@
 use indoc::indoc;
 use insta::assert_json_snapshot;
-use lithtml::{Dom, Result};
+use lithtml::{Dom, Node, ParseOptions, Result};
 
 #[test]
 fn it_can_parse_document_with_just_text() -> Result<()> {
@@ -60,3 +60,26 @@ fn it_can_parse_text_in_paragraph_with_weird_formatting() -> Result<()> {
     assert_json_snapshot!(dom);
     Ok(())
 }
+
+#[test]
+fn it_can_collapse_the_whitespace_of_text() -> Result<()> {
+    let options = ParseOptions {
+        collapse_whitespace: true,
+        ..ParseOptions::default()
+    };
+    let html =
+        "<p>Hello \n\t  world, <b>how\n are</b>   you?</p><pre>  keep\n   this  <b>a  b</b></pre>";
+    let dom = Dom::parse_with(html, &options)?;
+
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0], Node::new_text("Hello world, "));
+    assert_eq!(p.text_content(), "Hello world, how are you?");
+
+    let pre = dom.children[1].element().unwrap();
+    assert_eq!(pre.text_content(), "  keep\n   this  a  b");
+
+    let dom = Dom::parse(html)?;
+    let p = dom.children[0].element().unwrap();
+    assert_eq!(p.children[0], Node::new_text("Hello \n\t  world, "));
+    Ok(())
+}
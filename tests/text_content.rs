@@ -0,0 +1,87 @@
+use indoc::indoc;
+use lithtml::{Dom, Node, Result};
+
+#[test]
+fn it_can_render_text_with_spaces_between_inline_elements() -> Result<()> {
+    let dom = Dom::parse("<div><b>Hello</b><b>World</b></div>")?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.rendered_text(), "Hello World");
+    Ok(())
+}
+
+#[test]
+fn it_can_render_text_with_new_lines_for_block_elements() -> Result<()> {
+    let html = indoc!(
+        "<div>
+            <h1>Title</h1>
+            <p>First   paragraph with <a href='#'>a link</a>.</p>
+            <ul><li>one</li><li>two</li></ul>
+            Line<br>break
+            <script>var ignored = true;</script>
+            <!-- ignored -->
+        </div>"
+    );
+    let dom = Dom::parse(html)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(
+        div.rendered_text(),
+        "Title\nFirst paragraph with a link .\none\ntwo\nLine\nbreak"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_can_render_text_with_custom_block_elements() -> Result<()> {
+    let dom = Dom::parse("<div><span>a</span><SPAN>b</SPAN><p>c</p></div>")?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.rendered_text_with(&["span"]), "a\nb\nc");
+    Ok(())
+}
+
+#[test]
+fn it_can_count_words() -> Result<()> {
+    let html = indoc!(
+        "<article>
+            <h1>Counting words</h1>
+            <p>The quick brown fox jumps over the <b>lazy</b> dog.</p>
+            <script>let these = 'words are not counted';</script>
+            <style>p { color: red; }</style>
+        </article>
+        Four more root words"
+    );
+    let dom = Dom::parse(html)?;
+    assert_eq!(dom.word_count(), 15);
+    assert_eq!(dom.reading_time_minutes(5), 3.0);
+    assert_eq!(dom.reading_time_minutes(0), 0.0);
+    assert_eq!(Dom::parse("<script>var a = 1;</script>")?.word_count(), 0);
+    Ok(())
+}
+
+#[test]
+fn it_can_get_the_text_content() -> Result<()> {
+    let html = indoc!(
+        "<div>
+            <p>First <b>bold<!-- no --></b> text</p><p>Second</p>
+            <!-- comment -->
+            <ul><li>one</li><li>two<i>!</i></li></ul>
+        </div>"
+    );
+    let dom = Dom::parse(html)?;
+    let div = dom.children[0].element().unwrap();
+    assert_eq!(div.text_content(), "First bold textSecondonetwo!");
+    assert_eq!(div.text_content_spaced(), "First bold text Second one two!");
+    assert_eq!(dom.children[0].text_content(), div.text_content());
+
+    let dom = Dom::parse("<p>a</p><p> b </p>")?;
+    assert_eq!(dom.children[0].text_content_spaced(), "a");
+    let p = Node::Element(lithtml::Element {
+        name: "div".into(),
+        children: dom.children.clone(),
+        ..Default::default()
+    });
+    assert_eq!(p.text_content(), "a b ");
+    assert_eq!(p.text_content_spaced(), "a b ");
+    assert_eq!(Node::new_text("text").text_content(), "text");
+    assert_eq!(Node::new_comment("comment").text_content(), "");
+    Ok(())
+}
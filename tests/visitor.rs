@@ -0,0 +1,67 @@
+use indoc::indoc;
+use lithtml::{Dom, Element, Result, Visitor};
+
+#[derive(Default)]
+struct Outline {
+    entries: Vec<(String, usize)>,
+}
+
+impl Visitor for Outline {
+    fn visit_element(&mut self, element: &Element, depth: usize) {
+        self.entries.push((element.name.to_string(), depth));
+    }
+
+    fn visit_text(&mut self, text: &str, depth: usize) {
+        self.entries.push((format!("'{}'", text.trim()), depth));
+    }
+
+    fn visit_comment(&mut self, comment: &str, depth: usize) {
+        self.entries.push((format!("<!--{comment}-->"), depth));
+    }
+}
+
+#[test]
+fn it_can_walk_the_dom_with_depth() -> Result<()> {
+    let html = indoc!(
+        "
+        <ul>
+            <li>one</li>
+            <li><b>two</b></li>
+        </ul>
+        <!-- end -->
+        <p>text</p>"
+    );
+    let dom = Dom::parse(html)?;
+    let mut outline = Outline::default();
+    dom.walk(&mut outline);
+
+    let expected = [
+        ("ul", 0),
+        ("li", 1),
+        ("'one'", 2),
+        ("''", 1),
+        ("li", 1),
+        ("b", 2),
+        ("'two'", 3),
+        ("<!-- end -->", 0),
+        ("p", 0),
+        ("'text'", 1),
+    ];
+    let expected: Vec<_> = expected.iter().map(|(n, d)| (n.to_string(), *d)).collect();
+    assert_eq!(outline.entries, expected);
+    Ok(())
+}
+
+#[test]
+fn it_can_walk_a_node() -> Result<()> {
+    let dom = Dom::parse("<div><p>a<i>b</i></p></div>")?;
+    let mut outline = Outline::default();
+    dom.children[0].element().unwrap().children[0].walk(&mut outline);
+    let entries: Vec<_> = outline
+        .entries
+        .iter()
+        .map(|(n, d)| (n.as_str(), *d))
+        .collect();
+    assert_eq!(entries, [("p", 0), ("'a'", 1), ("i", 1), ("'b'", 2)]);
+    Ok(())
+}